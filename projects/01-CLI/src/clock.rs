@@ -0,0 +1,28 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::Timestamp;
+
+/// Where `JiraDatabase` gets the time it stamps items and history entries with.
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        // a clock set before 1970 is not worth failing a write over
+        Timestamp(SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default())
+    }
+}
+
+/// Always returns the same time, so tests can assert on timestamps.
+#[cfg(test)]
+pub struct FixedClock(pub Timestamp);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> Timestamp {
+        self.0
+    }
+}
@@ -0,0 +1,549 @@
+use std::fmt::Display;
+use std::fs;
+use std::io::Write;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+
+use crate::db::{migrate_json_to_sqlite, ConflictError, JiraDatabase};
+use crate::export::{export, ExportFormat};
+use crate::import::{plan_import, write_summary};
+use crate::links::open_blockers;
+use crate::models::{Epic, Status, Story};
+use crate::ui::DEFAULT_PAGE_SIZE;
+use crate::workflow::Workflow;
+use crate::output::{epic_views, story_views, write_epic, write_epic_list, write_story, write_story_list, EpicView, OutputFormat, StoryView};
+
+pub const DEFAULT_DB_PATH: &str = "./data/db.json";
+
+pub const USAGE: &str = "usage:
+    jira [--db <path>] [--workflow <file>] [--page-size <rows>]         start the interactive UI
+    jira [--db <path>] epic create --name <name> [--description <text>]
+    jira [--db <path>] epic list [--format plain|json|table]
+    jira [--db <path>] epic show <epic-id> [--format plain|json|table]
+    jira [--db <path>] [--workflow <file>] epic status <epic-id> <open|in-progress|resolved|closed>
+    jira [--db <path>] epic delete <epic-id> --yes
+    jira [--db <path>] story create --epic <epic-id> --name <name> [--description <text>]
+    jira [--db <path>] story list --epic <epic-id> [--format plain|json|table]
+    jira [--db <path>] story show <story-id> [--format plain|json|table]
+    jira [--db <path>] [--workflow <file>] story status <story-id> <open|in-progress|resolved|closed>
+    jira [--db <path>] story delete <story-id> --yes
+    jira [--db <path>] export <csv|markdown> [--output <file>]           export every story, to stdout without --output
+    jira [--db <path>] import <file.csv> [--dry-run]                     add the epics and stories of a CSV export
+    jira migrate-to-sqlite <db.json> <db.sqlite>
+
+--workflow reads the allowed status changes from a JSON file such as
+{\"open\": [\"in-progress\"], \"in-progress\": [\"open\", \"resolved\"]}";
+
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_CONFLICT: i32 = 3;
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    EpicCreate { name: String, description: String },
+    EpicList,
+    EpicShow { epic_id: u32 },
+    EpicStatus { epic_id: u32, status: Status },
+    EpicDelete { epic_id: u32 },
+    StoryCreate { epic_id: u32, name: String, description: String },
+    StoryList { epic_id: u32 },
+    StoryShow { story_id: u32 },
+    StoryStatus { story_id: u32, status: Status },
+    StoryDelete { story_id: u32 },
+    // None writes to stdout
+    Export { format: ExportFormat, output: Option<String> },
+    Import { path: String, dry_run: bool },
+    MigrateToSqlite { json_path: String, sqlite_path: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Invocation {
+    pub db_path: String,
+    // None starts the interactive UI
+    pub command: Option<Command>,
+    pub format: OutputFormat,
+    // rows per page in the interactive UI
+    pub page_size: usize,
+    // None uses the default workflow
+    pub workflow_path: Option<String>
+}
+
+/// Returned for malformed command lines so `main` can exit with `EXIT_USAGE`.
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n\n{}", self.0, USAGE)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn usage_error(message: impl Into<String>) -> anyhow::Error {
+    UsageError(message.into()).into()
+}
+
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    if error.downcast_ref::<UsageError>().is_some() {
+        EXIT_USAGE
+    } else if error.downcast_ref::<ConflictError>().is_some() {
+        EXIT_CONFLICT
+    } else {
+        EXIT_ERROR
+    }
+}
+
+pub fn parse_args(args: &[String]) -> Result<Invocation> {
+    let mut args = args.to_vec();
+
+    let db_path = take_option(&mut args, "--db")?.unwrap_or_else(|| DEFAULT_DB_PATH.to_owned());
+    let workflow_path = take_option(&mut args, "--workflow")?;
+    let format = match take_option(&mut args, "--format")? {
+        Some(format) => format.parse().map_err(|error: anyhow::Error| usage_error(error.to_string()))?,
+        None => OutputFormat::default()
+    };
+
+    let page_size = match take_option(&mut args, "--page-size")? {
+        Some(page_size) => match page_size.parse() {
+            Ok(page_size) if page_size > 0 => page_size,
+            _ => return Err(usage_error(format!("invalid page size \"{}\"", page_size)))
+        },
+        None => DEFAULT_PAGE_SIZE
+    };
+
+    if args.is_empty() {
+        return Ok(Invocation { db_path, command: None, format, page_size, workflow_path });
+    }
+
+    let command = match (args[0].as_str(), args.get(1).map(String::as_str)) {
+        ("epic", Some("create")) => {
+            let name = take_required_option(&mut args, "--name")?;
+            let description = take_option(&mut args, "--description")?.unwrap_or_default();
+            expect_positionals(&args, 2)?;
+
+            Command::EpicCreate { name: validate_name(name)?, description }
+        }
+        ("epic", Some("list")) => {
+            expect_positionals(&args, 2)?;
+
+            Command::EpicList
+        }
+        ("epic", Some("show")) => {
+            expect_positionals(&args, 3)?;
+
+            Command::EpicShow { epic_id: parse_id(&args[2])? }
+        }
+        ("epic", Some("status")) => {
+            expect_positionals(&args, 4)?;
+
+            Command::EpicStatus { epic_id: parse_id(&args[2])?, status: parse_status(&args[3])? }
+        }
+        ("epic", Some("delete")) => {
+            let yes = take_flag(&mut args, "--yes");
+            expect_positionals(&args, 3)?;
+            require_confirmation(yes)?;
+
+            Command::EpicDelete { epic_id: parse_id(&args[2])? }
+        }
+        ("story", Some("create")) => {
+            let epic_id = parse_id(&take_required_option(&mut args, "--epic")?)?;
+            let name = take_required_option(&mut args, "--name")?;
+            let description = take_option(&mut args, "--description")?.unwrap_or_default();
+            expect_positionals(&args, 2)?;
+
+            Command::StoryCreate { epic_id, name: validate_name(name)?, description }
+        }
+        ("story", Some("list")) => {
+            let epic_id = parse_id(&take_required_option(&mut args, "--epic")?)?;
+            expect_positionals(&args, 2)?;
+
+            Command::StoryList { epic_id }
+        }
+        ("story", Some("show")) => {
+            expect_positionals(&args, 3)?;
+
+            Command::StoryShow { story_id: parse_id(&args[2])? }
+        }
+        ("story", Some("status")) => {
+            expect_positionals(&args, 4)?;
+
+            Command::StoryStatus { story_id: parse_id(&args[2])?, status: parse_status(&args[3])? }
+        }
+        ("story", Some("delete")) => {
+            let yes = take_flag(&mut args, "--yes");
+            expect_positionals(&args, 3)?;
+            require_confirmation(yes)?;
+
+            Command::StoryDelete { story_id: parse_id(&args[2])? }
+        }
+        ("export", _) => {
+            let output = take_option(&mut args, "--output")?;
+            expect_positionals(&args, 2)?;
+
+            let format = args[1].parse().map_err(|error: anyhow::Error| usage_error(error.to_string()))?;
+
+            Command::Export { format, output }
+        }
+        ("import", _) => {
+            let dry_run = take_flag(&mut args, "--dry-run");
+            expect_positionals(&args, 2)?;
+
+            Command::Import { path: args[1].clone(), dry_run }
+        }
+        ("migrate-to-sqlite", _) => {
+            expect_positionals(&args, 3)?;
+
+            Command::MigrateToSqlite { json_path: args[1].clone(), sqlite_path: args[2].clone() }
+        }
+        _ => return Err(usage_error(format!("unknown command \"{}\"", args.join(" "))))
+    };
+
+    Ok(Invocation { db_path, command: Some(command), format, page_size, workflow_path })
+}
+
+pub fn run_command<W: Write>(command: Command, format: OutputFormat, db: &JiraDatabase, writer: &mut W) -> Result<()> {
+    match command {
+        Command::EpicCreate { name, description } => {
+            let epic_id = db.create_epic(Epic::new(name, description))?;
+            writeln!(writer, "{}", epic_id)?;
+        }
+        Command::EpicList => {
+            let db_state = db.read_db()?;
+            write_epic_list(&epic_views(&db_state), format, writer)?;
+        }
+        Command::EpicShow { epic_id } => {
+            let db_state = db.read_db()?;
+            let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?;
+
+            write_epic(&EpicView::new(epic_id, epic), format, writer)?;
+        }
+        Command::EpicStatus { epic_id, status } => db.update_epic_status(epic_id, status)?,
+        Command::EpicDelete { epic_id } => db.delete_epic(epic_id)?,
+        Command::StoryCreate { epic_id, name, description } => {
+            let story_id = db.create_story(Story::new(name, description), epic_id)?;
+            writeln!(writer, "{}", story_id)?;
+        }
+        Command::StoryList { epic_id } => {
+            let db_state = db.read_db()?;
+            write_story_list(&story_views(&db_state, epic_id)?, format, writer)?;
+        }
+        Command::StoryShow { story_id } => {
+            let db_state = db.read_db()?;
+            let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?;
+
+            write_story(&StoryView::new(story_id, find_epic_of_story(db, story_id)?, story), format, writer)?;
+        }
+        Command::StoryStatus { story_id, status } => {
+            let blockers = if status == Status::Closed { open_blockers(&db.read_db()?, story_id) } else { vec![] };
+
+            db.update_story_status(story_id, status)?;
+
+            // closing is still allowed, the warning goes to stderr to keep the output scriptable
+            if !blockers.is_empty() {
+                eprintln!("Warning: story {} is still blocked by open stories {}", story_id, blockers.iter().join(", "));
+            }
+        }
+        Command::StoryDelete { story_id } => db.delete_story(find_epic_of_story(db, story_id)?, story_id)?,
+        Command::Export { format, output } => {
+            let db_state = db.read_db()?;
+
+            match output {
+                Some(path) => {
+                    let mut contents = vec![];
+                    export(&db_state, format, &mut contents)?;
+
+                    fs::write(&path, contents).with_context(|| format!("failed to write {}", path))?;
+                    writeln!(writer, "Exported {} stories to {}", db_state.stories.len(), path)?;
+                }
+                None => export(&db_state, format, writer)?
+            }
+        }
+        Command::Import { path, dry_run } => {
+            let contents = fs::read_to_string(&path).with_context(|| format!("failed to read {}", path))?;
+            let plan = plan_import(&db.read_db()?, &contents)?;
+
+            if dry_run {
+                write_summary(&plan, None, writer)?;
+            } else {
+                let ids = db.import(&plan)?;
+                write_summary(&plan, Some(&ids), writer)?;
+            }
+        }
+        Command::MigrateToSqlite { json_path, sqlite_path } => migrate_to_sqlite(&json_path, &sqlite_path, writer)?,
+    }
+
+    Ok(())
+}
+
+/// Runs `migrate-to-sqlite`. `main` calls it without opening the `--db` database first, the
+/// migration only touches its own two files and must be the one to create the SQLite file.
+pub fn migrate_to_sqlite<W: Write>(json_path: &str, sqlite_path: &str, writer: &mut W) -> Result<()> {
+    let (epic_count, story_count) = migrate_json_to_sqlite(json_path, sqlite_path)?;
+    writeln!(writer, "Migrated {} epics and {} stories from {} to {}", epic_count, story_count, json_path, sqlite_path)?;
+
+    Ok(())
+}
+
+/// Opens the database of an invocation with the workflow from `--workflow`, or the default one.
+pub fn open_database(db_path: String, workflow_path: Option<&str>) -> Result<JiraDatabase> {
+    let mut db = JiraDatabase::open(db_path)?;
+
+    if let Some(workflow_path) = workflow_path {
+        db.workflow = Workflow::load(workflow_path)?;
+    }
+
+    Ok(db)
+}
+
+fn find_epic_of_story(db: &JiraDatabase, story_id: u32) -> Result<u32> {
+    db.read_db()?.epics.iter()
+        .find(|(_, epic)| epic.stories.contains(&story_id))
+        .map(|(epic_id, _)| *epic_id)
+        .ok_or_else(|| anyhow!("Story {} not found in database", story_id))
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let position = args.iter().position(|arg| arg == flag);
+
+    if let Some(position) = position {
+        args.remove(position);
+    }
+
+    position.is_some()
+}
+
+fn take_option(args: &mut Vec<String>, option: &str) -> Result<Option<String>> {
+    let Some(position) = args.iter().position(|arg| arg == option) else {
+        return Ok(None);
+    };
+
+    if position + 1 >= args.len() {
+        return Err(usage_error(format!("{} needs a value", option)));
+    }
+
+    let value = args.remove(position + 1);
+    args.remove(position);
+
+    Ok(Some(value))
+}
+
+fn take_required_option(args: &mut Vec<String>, option: &str) -> Result<String> {
+    take_option(args, option)?.ok_or_else(|| usage_error(format!("missing required option {}", option)))
+}
+
+fn expect_positionals(args: &[String], count: usize) -> Result<()> {
+    if let Some(unexpected) = args.get(count) {
+        return Err(usage_error(format!("unexpected argument \"{}\"", unexpected)));
+    }
+
+    if args.len() < count {
+        return Err(usage_error(format!("missing arguments for \"{}\"", args.join(" "))));
+    }
+
+    Ok(())
+}
+
+fn require_confirmation(yes: bool) -> Result<()> {
+    if !yes {
+        return Err(usage_error("refusing to delete without --yes"));
+    }
+
+    Ok(())
+}
+
+fn validate_name(name: String) -> Result<String> {
+    let name = name.trim().to_owned();
+
+    if name.is_empty() {
+        return Err(usage_error("name cannot be empty"));
+    }
+
+    Ok(name)
+}
+
+fn parse_id(input: &str) -> Result<u32> {
+    input.parse().map_err(|_| usage_error(format!("invalid id \"{}\"", input)))
+}
+
+fn parse_status(input: &str) -> Result<Status> {
+    input.parse().map_err(|error: anyhow::Error| usage_error(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_utils::MockDB;
+
+    use super::*;
+
+    fn parse(args: &str) -> Result<Invocation> {
+        parse_args(&args.split_whitespace().map(str::to_owned).collect::<Vec<_>>())
+    }
+
+    fn parse_command(args: &str) -> Command {
+        parse(args).unwrap().command.unwrap()
+    }
+
+    fn run(db: &JiraDatabase, args: &str) -> Result<String> {
+        let invocation = parse(args)?;
+        let mut output = vec![];
+        run_command(invocation.command.unwrap(), invocation.format, db, &mut output)?;
+
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parse_args_should_start_interactive_ui_without_command() {
+        assert_eq!(parse("").unwrap(), Invocation { db_path: DEFAULT_DB_PATH.to_owned(), command: None, format: OutputFormat::Plain, page_size: DEFAULT_PAGE_SIZE, workflow_path: None });
+        assert_eq!(parse("--db other.json").unwrap(), Invocation { db_path: "other.json".to_owned(), command: None, format: OutputFormat::Plain, page_size: DEFAULT_PAGE_SIZE, workflow_path: None });
+        assert_eq!(parse("--page-size 25").unwrap().page_size, 25);
+        assert_eq!(parse("--workflow workflow.json").unwrap().workflow_path, Some("workflow.json".to_owned()));
+    }
+
+    #[test]
+    fn parse_args_should_accept_db_override_anywhere() {
+        let invocation = parse("epic list --db other.json").unwrap();
+
+        assert_eq!(invocation.db_path, "other.json".to_owned());
+        assert_eq!(invocation.command, Some(Command::EpicList));
+    }
+
+    #[test]
+    fn parse_args_should_parse_commands() {
+        assert_eq!(parse_command("epic create --name epic --description desc"),
+            Command::EpicCreate { name: "epic".to_owned(), description: "desc".to_owned() });
+        assert_eq!(parse_command("epic status 1 in-progress"), Command::EpicStatus { epic_id: 1, status: Status::InProgress });
+        assert_eq!(parse_command("epic delete 1 --yes"), Command::EpicDelete { epic_id: 1 });
+        assert_eq!(parse_command("story create --name story --epic 1"),
+            Command::StoryCreate { epic_id: 1, name: "story".to_owned(), description: "".to_owned() });
+        assert_eq!(parse_command("story list --epic 1"), Command::StoryList { epic_id: 1 });
+        assert_eq!(parse_command("story status 3 RESOLVED"), Command::StoryStatus { story_id: 3, status: Status::Resolved });
+        assert_eq!(parse_command("story delete 3 --yes"), Command::StoryDelete { story_id: 3 });
+        assert_eq!(parse_command("export md --output backlog.md"), Command::Export { format: ExportFormat::Markdown, output: Some("backlog.md".to_owned()) });
+        assert_eq!(parse_command("export csv"), Command::Export { format: ExportFormat::Csv, output: None });
+        assert_eq!(parse_command("import --dry-run backlog.csv"), Command::Import { path: "backlog.csv".to_owned(), dry_run: true });
+    }
+
+    #[test]
+    fn parse_args_should_return_usage_errors() {
+        for args in ["epic", "epic create", "epic create --name", "epic status 1 done", "epic show x",
+            "epic delete 1", "story list", "story show 1 2", "epic list --format yaml", "--page-size 0", "--page-size x", "export", "export xlsx", "export csv --output", "import", "import a.csv b.csv", "--workflow", "unknown"] {
+            let error = parse(args).unwrap_err();
+            assert_eq!(exit_code(&error), EXIT_USAGE, "{}", args);
+        }
+    }
+
+    #[test]
+    fn run_command_should_create_and_list_items() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        assert_eq!(run(&db, "epic create --name epic").unwrap(), "1\n");
+        assert_eq!(run(&db, "story create --epic 1 --name story").unwrap(), "2\n");
+        assert_eq!(run(&db, "story status 2 in-progress").unwrap(), "");
+
+        assert_eq!(run(&db, "epic list").unwrap(), "1\tOPEN\tepic\n");
+        assert_eq!(run(&db, "story list --epic 1").unwrap(), "2\tIN PROGRESS\tstory\n");
+    }
+
+    #[test]
+    fn run_command_should_output_json() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let epics: serde_json::Value = serde_json::from_str(&run(&db, "epic list --format json").unwrap()).unwrap();
+        let story: serde_json::Value = serde_json::from_str(&run(&db, "story show 2 --format json").unwrap()).unwrap();
+
+        assert_eq!(epics[0]["story_count"], 1);
+        assert_eq!(story["epic_id"], epic_id);
+    }
+
+    #[test]
+    fn run_command_should_delete_story_without_epic_id() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        run(&db, &format!("story delete {} --yes", story_id)).unwrap();
+
+        assert!(db.read_db().unwrap().stories.is_empty());
+    }
+
+    #[test]
+    fn run_command_should_fail_for_missing_items() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+
+        for args in ["epic show 1", "epic delete 1 --yes", "story show 1", "story list --epic 1", "story delete 1 --yes"] {
+            let error = run(&db, args).unwrap_err();
+            assert_eq!(exit_code(&error), EXIT_ERROR, "{}", args);
+        }
+    }
+
+    #[test]
+    fn run_command_should_export_to_stdout_or_file() {
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        db.create_story(Story::new("story, with comma".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let csv = run(&db, "export csv").unwrap();
+        assert_eq!(csv.lines().nth(1), Some("2,1,epic,\"story, with comma\",,OPEN,MEDIUM,,,"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backlog.md");
+
+        let output = run(&db, &format!("export markdown --output {}", path.display())).unwrap();
+
+        assert_eq!(output, format!("Exported 1 stories to {}\n", path.display()));
+        assert!(fs::read_to_string(&path).unwrap().contains("## 1 epic (OPEN)"));
+    }
+
+    #[test]
+    fn run_command_should_import_exported_csv() {
+        let source = JiraDatabase::with_database(Box::new(MockDB::new()));
+        let epic_id = source.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        source.create_story(Story::new("story, with comma".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backlog.csv");
+        run(&source, &format!("export csv --output {}", path.display())).unwrap();
+
+        let db = JiraDatabase::with_database(Box::new(MockDB::new()));
+        db.create_epic(Epic::new("other".to_owned(), "".to_owned())).unwrap();
+
+        let output = run(&db, &format!("import {} --dry-run", path.display())).unwrap();
+
+        assert_eq!(output, "Dry run, nothing was imported\nWould import 1 epics and 1 stories\n");
+        assert_eq!(db.read_db().unwrap().last_item_id, 1);
+
+        let output = run(&db, &format!("import {}", path.display())).unwrap();
+
+        assert_eq!(output, "Imported 1 epics and 1 stories\n  epic 1 \"epic\" -> 2\n  line 2 -> story 3\n");
+        assert_eq!(db.read_db().unwrap().stories.get(&3).unwrap().name, "story, with comma");
+    }
+
+    #[test]
+    fn migrate_to_sqlite_should_create_target_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+
+        let mut output = vec![];
+        migrate_to_sqlite("./data/db.json", &sqlite_path, &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), format!("Migrated 1 epics and 2 stories from ./data/db.json to {}\n", sqlite_path));
+        assert_eq!(JiraDatabase::open(sqlite_path).unwrap().read_db().unwrap().stories.len(), 2);
+    }
+
+    #[test]
+    fn open_database_should_use_workflow_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let workflow_path = dir.path().join("workflow.json").to_str().unwrap().to_owned();
+        fs::write(&workflow_path, r#"{ "open": ["closed"] }"#).unwrap();
+
+        let db = open_database("./data/db.json".to_owned(), Some(&workflow_path)).unwrap();
+        assert_eq!(db.workflow, Workflow::new().allow(Status::Open, Status::Closed));
+
+        assert_eq!(open_database("./data/db.json".to_owned(), None).unwrap().workflow, Workflow::default());
+        assert!(open_database("./data/db.json".to_owned(), Some("INVALID_PATH")).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Value};
+
+use crate::models::DBState;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+// MIGRATIONS[n] upgrades a document from schema version n to n + 1.
+// Append new steps here whenever the persisted format changes; never edit old ones.
+const MIGRATIONS: &[Migration] = &[
+    v0_to_v1,
+    v1_to_v2,
+    v2_to_v3,
+    v3_to_v4,
+    v4_to_v5,
+    v5_to_v6,
+    v6_to_v7,
+    v7_to_v8,
+    v8_to_v9,
+];
+
+pub const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64;
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Serializes `db_state` into the persisted document, stamped with the current schema version.
+pub fn to_document(db_state: &DBState) -> Result<Value> {
+    let mut document = serde_json::to_value(db_state)?;
+
+    document.as_object_mut().ok_or_else(|| anyhow!("database state must serialize to a JSON object"))?
+        .insert(SCHEMA_VERSION_KEY.to_owned(), SCHEMA_VERSION.into());
+
+    Ok(document)
+}
+
+/// Upgrades a persisted document to the current schema version and deserializes it.
+/// Documents written by a newer version are refused rather than silently downgraded.
+pub fn from_document(document: Value) -> Result<DBState> {
+    let Value::Object(mut document) = document else {
+        return Err(anyhow!("database document must be a JSON object"));
+    };
+
+    upgrade(&mut document)?;
+
+    Ok(serde_json::from_value(Value::Object(document))?)
+}
+
+fn upgrade(document: &mut Map<String, Value>) -> Result<()> {
+    // documents from before versioning was introduced have no version field
+    let version = match document.get(SCHEMA_VERSION_KEY) {
+        None => 0,
+        Some(value) => value.as_u64().ok_or_else(|| anyhow!("invalid schema version {}", value))?
+    };
+
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!("database uses schema version {} but this version of My-Jira only supports up to {}, \
+            please upgrade My-Jira instead of downgrading the database", version, SCHEMA_VERSION));
+    }
+
+    for (from_version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(document).with_context(|| format!("failed to migrate database from schema version {} to {}", from_version, from_version + 1))?;
+        document.insert(SCHEMA_VERSION_KEY.to_owned(), (from_version as u64 + 1).into());
+    }
+
+    Ok(())
+}
+
+// v1 introduced the schema version itself and the revision counter used for conflict detection
+fn v0_to_v1(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("revision").or_insert(0.into());
+
+    Ok(())
+}
+
+// v2 added the planning fields to stories: assignee, priority, story points and due date
+fn v1_to_v2(document: &mut Map<String, Value>) -> Result<()> {
+    let Some(stories) = document.get_mut("stories").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    for story in stories.values_mut() {
+        let story = story.as_object_mut().ok_or_else(|| anyhow!("story must be a JSON object"))?;
+
+        story.entry("assignee").or_insert(Value::Null);
+        story.entry("priority").or_insert("Medium".into());
+        story.entry("story_points").or_insert(Value::Null);
+        story.entry("due_date").or_insert(Value::Null);
+    }
+
+    Ok(())
+}
+
+// v3 added creation and update timestamps and the change history to epics and stories
+fn v2_to_v3(document: &mut Map<String, Value>) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let Some(items) = document.get_mut(items).and_then(Value::as_object_mut) else {
+            continue;
+        };
+
+        for item in items.values_mut() {
+            let item = item.as_object_mut().ok_or_else(|| anyhow!("epics and stories must be JSON objects"))?;
+
+            // the real creation time of older items is unknown
+            item.entry("created_at").or_insert(Value::Null);
+            item.entry("updated_at").or_insert(Value::Null);
+            item.entry("history").or_insert(Value::Array(vec![]));
+        }
+    }
+
+    Ok(())
+}
+
+// v4 added comments to epics and stories
+fn v3_to_v4(document: &mut Map<String, Value>) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let Some(items) = document.get_mut(items).and_then(Value::as_object_mut) else {
+            continue;
+        };
+
+        for item in items.values_mut() {
+            let item = item.as_object_mut().ok_or_else(|| anyhow!("epics and stories must be JSON objects"))?;
+
+            item.entry("comments").or_insert(Value::Array(vec![]));
+        }
+    }
+
+    Ok(())
+}
+
+// v5 added the checklist to stories
+fn v4_to_v5(document: &mut Map<String, Value>) -> Result<()> {
+    let Some(stories) = document.get_mut("stories").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    for story in stories.values_mut() {
+        let story = story.as_object_mut().ok_or_else(|| anyhow!("story must be a JSON object"))?;
+
+        story.entry("checklist").or_insert(Value::Array(vec![]));
+    }
+
+    Ok(())
+}
+
+// v6 added the links between stories
+fn v5_to_v6(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("links").or_insert(Value::Array(vec![]));
+
+    Ok(())
+}
+
+// v7 added sprints and the sprint a story is planned into
+fn v6_to_v7(document: &mut Map<String, Value>) -> Result<()> {
+    document.entry("sprints").or_insert(Value::Object(Map::new()));
+
+    let Some(stories) = document.get_mut("stories").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    for story in stories.values_mut() {
+        let story = story.as_object_mut().ok_or_else(|| anyhow!("story must be a JSON object"))?;
+
+        story.entry("sprint_id").or_insert(Value::Null);
+    }
+
+    Ok(())
+}
+
+// v8 added the opt-in to derive an epic's status from its stories
+fn v7_to_v8(document: &mut Map<String, Value>) -> Result<()> {
+    let Some(epics) = document.get_mut("epics").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    for epic in epics.values_mut() {
+        let epic = epic.as_object_mut().ok_or_else(|| anyhow!("epic must be a JSON object"))?;
+
+        epic.entry("derive_status").or_insert(Value::Bool(false));
+    }
+
+    Ok(())
+}
+
+// v9 added creation and update timestamps and the change history to sprints
+fn v8_to_v9(document: &mut Map<String, Value>) -> Result<()> {
+    let Some(sprints) = document.get_mut("sprints").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    for sprint in sprints.values_mut() {
+        let sprint = sprint.as_object_mut().ok_or_else(|| anyhow!("sprint must be a JSON object"))?;
+
+        sprint.entry("created_at").or_insert(Value::Null);
+        sprint.entry("updated_at").or_insert(Value::Null);
+        sprint.entry("history").or_insert(Value::Array(vec![]));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::models::{Priority, Timestamp};
+
+    use super::*;
+
+    fn read_fixture(name: &str) -> Map<String, Value> {
+        let contents = fs::read_to_string(format!("./tests/fixtures/{}", name)).unwrap();
+        serde_json::from_str(&contents).unwrap()
+    }
+
+    // the unversioned fixture with the first `version` migrations applied
+    fn migrated_to(version: usize) -> Map<String, Value> {
+        let mut document = read_fixture("db_v0.json");
+
+        for migration in &MIGRATIONS[..version] {
+            migration(&mut document).unwrap();
+        }
+
+        document
+    }
+
+    #[test]
+    fn from_document_should_upgrade_unversioned_fixture() {
+        let db_state = from_document(Value::Object(read_fixture("db_v0.json"))).unwrap();
+
+        assert_eq!(db_state.last_item_id, 3);
+        assert_eq!(db_state.revision, 0);
+        assert_eq!(db_state.epics.len(), 1);
+        assert_eq!(db_state.stories.len(), 2);
+    }
+
+    #[test]
+    fn v0_to_v1_should_add_revision() {
+        let mut document = migrated_to(0);
+
+        v0_to_v1(&mut document).unwrap();
+
+        assert_eq!(document.get("revision"), Some(&Value::from(0)));
+    }
+
+    #[test]
+    fn v1_to_v2_should_default_story_planning_fields() {
+        let mut document = migrated_to(1);
+
+        v1_to_v2(&mut document).unwrap();
+
+        let story = document["stories"]["2"].as_object().unwrap();
+
+        assert_eq!(story.get("assignee"), Some(&Value::Null));
+        assert_eq!(story.get("priority"), Some(&Value::from("Medium")));
+        assert_eq!(story.get("story_points"), Some(&Value::Null));
+        assert_eq!(story.get("due_date"), Some(&Value::Null));
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+        assert_eq!(db_state.stories[&2].priority, Priority::Medium);
+    }
+
+    #[test]
+    fn v2_to_v3_should_add_empty_history() {
+        let mut document = migrated_to(2);
+
+        v2_to_v3(&mut document).unwrap();
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+
+        assert_eq!(db_state.epics[&1].created_at, None);
+        assert!(db_state.epics[&1].history.is_empty());
+        assert_eq!(db_state.stories[&2].updated_at, None);
+        assert!(db_state.stories[&2].history.is_empty());
+    }
+
+    #[test]
+    fn v3_to_v4_should_add_empty_comments() {
+        let mut document = migrated_to(3);
+
+        v3_to_v4(&mut document).unwrap();
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+
+        assert!(db_state.epics[&1].comments.is_empty());
+        assert!(db_state.stories[&2].comments.is_empty());
+    }
+
+    #[test]
+    fn v4_to_v5_should_add_empty_checklist() {
+        let mut document = migrated_to(4);
+
+        v4_to_v5(&mut document).unwrap();
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+
+        assert!(db_state.stories[&2].checklist.is_empty());
+    }
+
+    #[test]
+    fn v5_to_v6_should_add_empty_links() {
+        let mut document = migrated_to(5);
+
+        v5_to_v6(&mut document).unwrap();
+
+        assert!(from_document(Value::Object(document)).unwrap().links.is_empty());
+    }
+
+    #[test]
+    fn v6_to_v7_should_add_sprints() {
+        let mut document = migrated_to(6);
+
+        v6_to_v7(&mut document).unwrap();
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+
+        assert!(db_state.sprints.is_empty());
+        assert_eq!(db_state.stories[&2].sprint_id, None);
+    }
+
+    #[test]
+    fn v7_to_v8_should_keep_epic_status_manual() {
+        let mut document = migrated_to(7);
+
+        v7_to_v8(&mut document).unwrap();
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+
+        assert!(!db_state.epics[&1].derive_status);
+    }
+
+    #[test]
+    fn v8_to_v9_should_add_sprint_history() {
+        let mut document = migrated_to(8);
+
+        document["sprints"] = serde_json::json!({ "4": { "name": "sprint", "goal": "", "start_date": "2024-06-03", "end_date": "2024-06-14", "state": "Planned" } });
+
+        v8_to_v9(&mut document).unwrap();
+
+        let db_state = from_document(Value::Object(document)).unwrap();
+
+        assert_eq!(db_state.sprints[&4].created_at, None);
+        assert!(db_state.sprints[&4].history.is_empty());
+    }
+
+    #[test]
+    fn from_document_should_upgrade_every_intermediate_version() {
+        let expected = from_document(Value::Object(read_fixture("db_v0.json"))).unwrap();
+
+        for version in 1..=MIGRATIONS.len() {
+            let mut document = migrated_to(version);
+            document.insert(SCHEMA_VERSION_KEY.to_owned(), version.into());
+
+            assert_eq!(from_document(Value::Object(document)).unwrap(), expected, "schema version {}", version);
+        }
+    }
+
+    #[test]
+    fn from_document_should_upgrade_v4_fixture() {
+        // written by the version that introduced comments
+        let db_state = from_document(Value::Object(read_fixture("db_v4.json"))).unwrap();
+
+        let epic = &db_state.epics[&1];
+        let story = &db_state.stories[&2];
+
+        assert_eq!(db_state.revision, 8);
+        assert_eq!(epic.created_at, Some(Timestamp(1717400000)));
+        assert_eq!(epic.history.len(), 3);
+        assert_eq!(epic.comments[0].body, "Kick-off on Monday");
+        assert!(!epic.derive_status);
+
+        assert_eq!(story.assignee, Some("alice".to_owned()));
+        assert_eq!(story.priority, Priority::High);
+        assert_eq!(story.story_points, Some(5));
+        assert_eq!(story.history.len(), 5);
+        assert_eq!(story.comments[0].author, "bob");
+        assert!(story.checklist.is_empty());
+        assert_eq!(story.sprint_id, None);
+
+        assert!(db_state.links.is_empty());
+        assert!(db_state.sprints.is_empty());
+    }
+
+    #[test]
+    fn upgrade_should_stamp_current_version() {
+        let mut document = read_fixture("db_v0.json");
+
+        upgrade(&mut document).unwrap();
+
+        assert_eq!(document.get(SCHEMA_VERSION_KEY), Some(&Value::from(SCHEMA_VERSION)));
+    }
+
+    #[test]
+    fn from_document_should_refuse_newer_schema_version() {
+        let mut document = read_fixture("db_v0.json");
+        document.insert(SCHEMA_VERSION_KEY.to_owned(), (SCHEMA_VERSION + 1).into());
+
+        let error = from_document(Value::Object(document)).unwrap_err();
+
+        assert!(error.to_string().contains("please upgrade My-Jira"));
+    }
+
+    #[test]
+    fn to_document_should_round_trip() {
+        let db_state = from_document(Value::Object(read_fixture("db_v0.json"))).unwrap();
+        let document = to_document(&db_state).unwrap();
+
+        assert_eq!(document.get(SCHEMA_VERSION_KEY), Some(&Value::from(SCHEMA_VERSION)));
+        assert_eq!(from_document(document).unwrap(), db_state);
+    }
+}
@@ -0,0 +1,584 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, Transaction, TransactionBehavior};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::models::DBState;
+
+use super::{migrations, ConflictError, Database, JSONFileDatabase};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        last_item_id INTEGER NOT NULL,
+        revision INTEGER NOT NULL,
+        extra TEXT NOT NULL DEFAULT '{}'
+    );
+
+    INSERT OR IGNORE INTO meta (id, last_item_id, revision) VALUES (1, 0, 0);
+
+    CREATE TABLE IF NOT EXISTS epics (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL,
+        created_at INTEGER,
+        updated_at INTEGER,
+        derive_status INTEGER NOT NULL DEFAULT 0,
+        extra TEXT NOT NULL DEFAULT '{}'
+    );
+
+    CREATE TABLE IF NOT EXISTS stories (
+        id INTEGER PRIMARY KEY,
+        epic_id INTEGER NOT NULL REFERENCES epics(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL,
+        assignee TEXT,
+        priority TEXT NOT NULL DEFAULT 'Medium',
+        story_points INTEGER,
+        due_date TEXT,
+        created_at INTEGER,
+        updated_at INTEGER,
+        sprint_id INTEGER,
+        extra TEXT NOT NULL DEFAULT '{}'
+    );
+
+    CREATE INDEX IF NOT EXISTS epics_status_idx ON epics (status);
+    CREATE INDEX IF NOT EXISTS stories_status_idx ON stories (status);
+    CREATE INDEX IF NOT EXISTS stories_epic_id_idx ON stories (epic_id);
+";
+
+// bumped through `PRAGMA user_version` whenever the table layout changes
+const LAYOUT_VERSION: i64 = 1;
+
+// layout 1 moved these out of `extra` into real columns; (table, column, declaration)
+const LAYOUT_V1_COLUMNS: [(&str, &str, &str); 10] = [
+    ("epics", "created_at", "INTEGER"),
+    ("epics", "updated_at", "INTEGER"),
+    ("epics", "derive_status", "INTEGER NOT NULL DEFAULT 0"),
+    ("stories", "assignee", "TEXT"),
+    ("stories", "priority", "TEXT NOT NULL DEFAULT 'Medium'"),
+    ("stories", "story_points", "INTEGER"),
+    ("stories", "due_date", "TEXT"),
+    ("stories", "created_at", "INTEGER"),
+    ("stories", "updated_at", "INTEGER"),
+    ("stories", "sprint_id", "INTEGER"),
+];
+
+// model fields that get a real column; anything else a model carries (history, comments, checklist) is kept as JSON in `extra`
+const EPIC_COLUMNS: [&str; 6] = ["name", "description", "status", "created_at", "updated_at", "derive_status"];
+const STORY_COLUMNS: [&str; 10] = ["name", "description", "status", "assignee", "priority", "story_points", "due_date", "created_at", "updated_at", "sprint_id"];
+
+// stored as 0 or 1
+const BOOLEAN_COLUMNS: [&str; 1] = ["derive_status"];
+
+// (id, parent id, fields) of one epic or story row
+type ItemRow = (u32, u32, Map<String, Value>);
+
+pub struct SqliteDatabase {
+    connection: Connection,
+    // the state as of the last read or write, to find the rows a write changes
+    cache: RefCell<Option<DBState>>
+}
+
+impl SqliteDatabase {
+    /// Opens an existing database. Only `create` makes a new file, so a mistyped path is an error.
+    pub fn open(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Err(anyhow!("database {} does not exist, create it with `jira migrate-to-sqlite`", path));
+        }
+
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+
+        Self::init(Connection::open_with_flags(path, flags).with_context(|| format!("failed to open {}", path))?)
+    }
+
+    /// Opens the database at `path`, creating the file if there is none.
+    pub fn create(path: &str) -> Result<Self> {
+        Self::init(Connection::open(path).with_context(|| format!("failed to create {}", path))?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self> {
+        connection.pragma_update(None, "foreign_keys", "ON")?;
+        connection.execute_batch(SCHEMA)?;
+
+        let layout_version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        if layout_version < LAYOUT_VERSION {
+            upgrade_layout(&connection)?;
+        }
+
+        Ok(Self { connection, cache: RefCell::new(None) })
+    }
+
+    fn is_empty(&self) -> Result<bool> {
+        let epic_count: u32 = self.connection.query_row("SELECT COUNT(*) FROM epics", [], |row| row.get(0))?;
+        let last_item_id: u32 = self.connection.query_row("SELECT last_item_id FROM meta", [], |row| row.get(0))?;
+
+        Ok(epic_count == 0 && last_item_id == 0)
+    }
+}
+
+impl Database for SqliteDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        let transaction = Transaction::new_unchecked(&self.connection, TransactionBehavior::Deferred)?;
+        let state = read_state(&transaction)?;
+        transaction.commit()?;
+
+        *self.cache.borrow_mut() = Some(state.clone());
+
+        Ok(state)
+    }
+
+    // no lock() override: SQLite serializes writers itself and write_db rejects stale revisions
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        // IMMEDIATE takes the write lock up front so the revision check below can't race
+        let transaction = Transaction::new_unchecked(&self.connection, TransactionBehavior::Immediate)?;
+
+        let (found_revision, extra): (u64, String) = transaction.query_row("SELECT revision, extra FROM meta", [], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        if found_revision != db_state.revision {
+            return Err(ConflictError { expected_revision: db_state.revision, found_revision }.into());
+        }
+
+        // rows of an older schema version have to be rewritten along with the version stamp in `meta`
+        let rewrite_all = stored_schema_version(&extra)? != migrations::SCHEMA_VERSION;
+
+        // every revision bump goes through a write, so a cached state at the found revision is what's stored
+        let previous = match self.cache.borrow_mut().take() {
+            Some(previous) if previous.revision == found_revision => previous,
+            _ => read_state(&transaction)?
+        };
+
+        let document = migrations::to_document(db_state)?;
+        let extra = extra_json(document, &["last_item_id", "revision", "epics", "stories"])?;
+
+        transaction.execute("UPDATE meta SET last_item_id = ?1, revision = ?2, extra = ?3",
+            params![db_state.last_item_id, db_state.revision + 1, extra])?;
+
+        write_changes(&transaction, &previous, db_state, rewrite_all)?;
+
+        transaction.commit()?;
+
+        *self.cache.borrow_mut() = Some(DBState { revision: db_state.revision + 1, ..db_state.clone() });
+
+        Ok(())
+    }
+}
+
+/// Copies every epic and story of a JSON database file into an empty SQLite
+/// database, creating it if needed and keeping their ids. Returns the number of epics and stories copied.
+pub fn migrate_json_to_sqlite(json_path: &str, sqlite_path: &str) -> Result<(usize, usize)> {
+    let json_db = JSONFileDatabase::new(json_path.to_owned(), 0);
+    let mut db_state = json_db.read_db().with_context(|| format!("failed to read {}", json_path))?;
+
+    let sqlite_db = SqliteDatabase::create(sqlite_path)?;
+
+    if !sqlite_db.is_empty()? {
+        return Err(anyhow!("{} already contains data, refusing to migrate into it", sqlite_path));
+    }
+
+    db_state.revision = sqlite_db.read_db()?.revision;
+
+    sqlite_db.write_db(&db_state)?;
+
+    Ok((db_state.epics.len(), db_state.stories.len()))
+}
+
+// assembles the same document the JSON backend stores so schema migrations apply to both
+fn read_state(connection: &Connection) -> Result<DBState> {
+    let (last_item_id, revision, extra): (u32, u64, String) = connection.query_row(
+        "SELECT last_item_id, revision, extra FROM meta", [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+    let mut epics = Map::new();
+
+    for (id, _, mut epic) in read_items(connection, "SELECT * FROM epics")? {
+        epic.insert("stories".to_owned(), Value::Array(vec![]));
+        epics.insert(id.to_string(), Value::Object(epic));
+    }
+
+    let mut stories = Map::new();
+
+    for (id, epic_id, story) in read_items(connection, "SELECT * FROM stories ORDER BY epic_id, position")? {
+        epics.get_mut(&epic_id.to_string()).and_then(|epic| epic["stories"].as_array_mut())
+            .ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?.push(id.into());
+        stories.insert(id.to_string(), Value::Object(story));
+    }
+
+    let mut state: Map<String, Value> = serde_json::from_str(&extra)?;
+    state.insert("last_item_id".to_owned(), last_item_id.into());
+    state.insert("revision".to_owned(), revision.into());
+    state.insert("epics".to_owned(), Value::Object(epics));
+    state.insert("stories".to_owned(), Value::Object(stories));
+
+    migrations::from_document(Value::Object(state))
+}
+
+// reads every column there is, so rows from before a column existed still come back whole from `extra`
+fn read_items(connection: &Connection, sql: &str) -> Result<Vec<ItemRow>> {
+    let mut statement = connection.prepare(sql)?;
+    let column_names: Vec<String> = statement.column_names().into_iter().map(str::to_owned).collect();
+
+    let mut rows = statement.query([])?;
+    let mut items = vec![];
+
+    while let Some(row) = rows.next()? {
+        let mut id = 0;
+        let mut parent_id = 0;
+        let mut fields = Map::new();
+        let mut columns = vec![];
+
+        for (index, name) in column_names.iter().enumerate() {
+            match name.as_str() {
+                "id" => id = row.get(index)?,
+                "epic_id" => parent_id = row.get(index)?,
+                "position" => {}
+                "extra" => fields = serde_json::from_str(&row.get::<_, String>(index)?)?,
+                _ => columns.push((name, row.get::<_, SqlValue>(index)?))
+            }
+        }
+
+        // a NULL column is a `None` field, which serde also reads from a missing key
+        for (name, value) in columns {
+            let value = match value {
+                SqlValue::Null => continue,
+                SqlValue::Integer(value) if BOOLEAN_COLUMNS.contains(&name.as_str()) => Value::Bool(value != 0),
+                SqlValue::Integer(value) => value.into(),
+                SqlValue::Text(value) => value.into(),
+                other => return Err(anyhow!("column {} has unexpected value {:?}", name, other))
+            };
+
+            fields.insert(name.clone(), value);
+        }
+
+        items.push((id, parent_id, fields));
+    }
+
+    Ok(items)
+}
+
+// writes only the epics and stories that differ from `previous`, or all of them with `rewrite_all`
+fn write_changes(transaction: &Transaction, previous: &DBState, db_state: &DBState, rewrite_all: bool) -> Result<()> {
+    let placements = story_placements(db_state)?;
+    let previous_placements = story_placements(previous)?;
+
+    let upsert_epic = upsert_sql("epics", &["id"], &EPIC_COLUMNS);
+    let upsert_story = upsert_sql("stories", &["id", "epic_id", "position"], &STORY_COLUMNS);
+
+    for (id, epic) in &db_state.epics {
+        if rewrite_all || previous.epics.get(id) != Some(epic) {
+            let mut values = vec![SqlValue::Integer((*id).into())];
+            values.extend(row_values(epic, &EPIC_COLUMNS, &["stories"])?);
+
+            transaction.prepare_cached(&upsert_epic)?.execute(params_from_iter(values))?;
+        }
+    }
+
+    for id in previous.stories.keys().filter(|id| !db_state.stories.contains_key(id)) {
+        transaction.execute("DELETE FROM stories WHERE id = ?1", params![id])?;
+    }
+
+    for (id, story) in &db_state.stories {
+        let placement = placements[id];
+
+        if rewrite_all || previous.stories.get(id) != Some(story) || previous_placements.get(id) != Some(&placement) {
+            let mut values = vec![SqlValue::Integer((*id).into()), SqlValue::Integer(placement.0.into()), SqlValue::Integer(placement.1 as i64)];
+            values.extend(row_values(story, &STORY_COLUMNS, &[])?);
+
+            transaction.prepare_cached(&upsert_story)?.execute(params_from_iter(values))?;
+        }
+    }
+
+    for id in previous.epics.keys().filter(|id| !db_state.epics.contains_key(id)) {
+        transaction.execute("DELETE FROM epics WHERE id = ?1", params![id])?;
+    }
+
+    Ok(())
+}
+
+// the epic and position of every story
+fn story_placements(db_state: &DBState) -> Result<HashMap<u32, (u32, usize)>> {
+    let mut placements = HashMap::new();
+
+    for (epic_id, epic) in &db_state.epics {
+        for (position, story_id) in epic.stories.iter().enumerate() {
+            if !db_state.stories.contains_key(story_id) {
+                return Err(anyhow!("Story {} not found in database", story_id));
+            }
+
+            placements.insert(*story_id, (*epic_id, position));
+        }
+    }
+
+    if placements.len() != db_state.stories.len() {
+        return Err(anyhow!("every story must belong to exactly one epic"));
+    }
+
+    Ok(placements)
+}
+
+// moves the columns added by a newer layout out of `extra`, rewriting every row once
+fn upgrade_layout(connection: &Connection) -> Result<()> {
+    let transaction = Transaction::new_unchecked(connection, TransactionBehavior::Immediate)?;
+
+    // read before adding columns, their defaults would otherwise hide the values still in `extra`
+    let db_state = read_state(&transaction)?;
+
+    for (table, column, declaration) in LAYOUT_V1_COLUMNS {
+        let exists: bool = transaction.query_row(&format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1", table), [column], |row| row.get(0))?;
+
+        if !exists {
+            transaction.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, declaration), [])?;
+        }
+    }
+
+    transaction.execute("CREATE INDEX IF NOT EXISTS stories_sprint_id_idx ON stories (sprint_id)", [])?;
+
+    let extra = extra_json(migrations::to_document(&db_state)?, &["last_item_id", "revision", "epics", "stories"])?;
+    transaction.execute("UPDATE meta SET extra = ?1", params![extra])?;
+
+    write_changes(&transaction, &db_state, &db_state, true)?;
+
+    transaction.pragma_update(None, "user_version", LAYOUT_VERSION)?;
+    transaction.commit()?;
+
+    Ok(())
+}
+
+fn upsert_sql(table: &str, keys: &[&str], columns: &[&str]) -> String {
+    let all_columns = keys.iter().chain(columns).chain(&["extra"]).collect_vec();
+    let placeholders = (1..=all_columns.len()).map(|index| format!("?{}", index)).join(", ");
+    let updates = all_columns.iter().skip(1).map(|column| format!("{} = excluded.{}", column, column)).join(", ");
+
+    format!("INSERT INTO {} ({}) VALUES ({}) ON CONFLICT (id) DO UPDATE SET {}", table, all_columns.iter().join(", "), placeholders, updates)
+}
+
+/// Serializes `item` and returns the values of `columns` followed by what's left, minus the `skip` fields, as JSON for `extra`.
+fn row_values<T: Serialize>(item: &T, columns: &[&str], skip: &[&str]) -> Result<Vec<SqlValue>> {
+    let Value::Object(mut fields) = serde_json::to_value(item)? else {
+        return Err(anyhow!("expected a JSON object"));
+    };
+
+    let mut values = vec![];
+
+    for column in columns {
+        values.push(match fields.remove(*column).unwrap_or(Value::Null) {
+            Value::Null => SqlValue::Null,
+            Value::Bool(value) => SqlValue::Integer(value.into()),
+            Value::String(value) => SqlValue::Text(value),
+            Value::Number(value) if value.is_u64() || value.is_i64() => SqlValue::Integer(value.as_i64().ok_or_else(|| anyhow!("column {} is out of range", column))?),
+            other => return Err(anyhow!("column {} has unexpected value {:?}", column, other))
+        });
+    }
+
+    values.push(SqlValue::Text(extra_json(Value::Object(fields), skip)?));
+
+    Ok(values)
+}
+
+fn extra_json(document: Value, skip: &[&str]) -> Result<String> {
+    let Value::Object(mut fields) = document else {
+        return Err(anyhow!("expected a JSON object"));
+    };
+
+    for key in skip {
+        fields.remove(*key);
+    }
+
+    Ok(Value::Object(fields).to_string())
+}
+
+fn stored_schema_version(extra: &str) -> Result<u64> {
+    let extra: Map<String, Value> = serde_json::from_str(extra)?;
+
+    Ok(extra.get(migrations::SCHEMA_VERSION_KEY).and_then(Value::as_u64).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::models::{Date, Epic, LinkType, Priority, Sprint, Status, Story, StoryLink};
+
+    use super::*;
+
+    fn sample_state() -> DBState {
+        let mut epic = Epic::new("epic 1".to_owned(), "epic 1".to_owned());
+        epic.status = Status::InProgress;
+        epic.stories = vec![3, 2];
+
+        let mut story = Story::new("story 3".to_owned(), "story 3".to_owned());
+        story.status = Status::Closed;
+
+        DBState {
+            last_item_id: 3,
+            revision: 0,
+            epics: HashMap::from([(1, epic)]),
+            stories: HashMap::from([(2, Story::new("story 2".to_owned(), "story 2".to_owned())), (3, story)]),
+            links: vec![StoryLink { from: 3, to: 2, link_type: LinkType::Blocks }],
+            sprints: HashMap::from([(4, Sprint::new("sprint 1".to_owned(), "".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap()))])
+        }
+    }
+
+    #[test]
+    fn write_db_should_round_trip_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+
+        let state = sample_state();
+        SqliteDatabase::create(&path).unwrap().write_db(&state).unwrap();
+
+        let read_state = SqliteDatabase::open(&path).unwrap().read_db().unwrap();
+
+        assert_eq!(read_state, DBState { revision: 1, ..state });
+    }
+
+    #[test]
+    fn open_should_refuse_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("typo.sqlite").to_str().unwrap().to_owned();
+
+        let error = SqliteDatabase::open(&path).err().unwrap();
+
+        assert!(error.to_string().contains("does not exist"));
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[test]
+    fn write_db_should_only_write_changed_rows() {
+        let db = SqliteDatabase::open_in_memory().unwrap();
+        db.write_db(&sample_state()).unwrap();
+
+        let mut state = db.read_db().unwrap();
+        state.stories.get_mut(&2).unwrap().assignee = Some("ann".to_owned());
+
+        let changes_before: u64 = db.connection.query_row("SELECT total_changes()", [], |row| row.get(0)).unwrap();
+        db.write_db(&state).unwrap();
+        let changes_after: u64 = db.connection.query_row("SELECT total_changes()", [], |row| row.get(0)).unwrap();
+
+        // the meta row and story 2
+        assert_eq!(changes_after - changes_before, 2);
+
+        let assignee: String = db.connection.query_row("SELECT assignee FROM stories WHERE id = 2", [], |row| row.get(0)).unwrap();
+        assert_eq!(assignee, "ann");
+        assert_eq!(db.read_db().unwrap(), DBState { revision: 2, ..state });
+    }
+
+    #[test]
+    fn write_db_should_delete_removed_rows() {
+        let db = SqliteDatabase::open_in_memory().unwrap();
+        db.write_db(&sample_state()).unwrap();
+
+        let mut state = db.read_db().unwrap();
+        state.stories.remove(&3);
+        state.epics.get_mut(&1).unwrap().stories = vec![2];
+        state.links.clear();
+        db.write_db(&state).unwrap();
+
+        let story_count: u32 = db.connection.query_row("SELECT COUNT(*) FROM stories", [], |row| row.get(0)).unwrap();
+
+        assert_eq!(story_count, 1);
+        assert_eq!(db.read_db().unwrap(), DBState { revision: 2, ..state });
+    }
+
+    #[test]
+    fn open_should_move_fields_of_old_layout_into_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+
+        // the layout before the planning fields had columns, with an unversioned document
+        Connection::open(&path).unwrap().execute_batch("
+            CREATE TABLE meta (id INTEGER PRIMARY KEY CHECK (id = 1), last_item_id INTEGER NOT NULL, revision INTEGER NOT NULL, extra TEXT NOT NULL DEFAULT '{}');
+            INSERT INTO meta (id, last_item_id, revision) VALUES (1, 2, 5);
+            CREATE TABLE epics (id INTEGER PRIMARY KEY, name TEXT NOT NULL, description TEXT NOT NULL, status TEXT NOT NULL, extra TEXT NOT NULL DEFAULT '{}');
+            CREATE TABLE stories (id INTEGER PRIMARY KEY, epic_id INTEGER NOT NULL REFERENCES epics(id) ON DELETE CASCADE, position INTEGER NOT NULL,
+                name TEXT NOT NULL, description TEXT NOT NULL, status TEXT NOT NULL, extra TEXT NOT NULL DEFAULT '{}');
+            INSERT INTO epics (id, name, description, status) VALUES (1, 'epic', '', 'Open');
+            INSERT INTO stories (id, epic_id, position, name, description, status, extra)
+                VALUES (2, 1, 0, 'story', '', 'Open', '{\"assignee\": \"ann\", \"priority\": \"High\", \"story_points\": 3, \"due_date\": null}');
+        ").unwrap();
+
+        let db = SqliteDatabase::open(&path).unwrap();
+        let state = db.read_db().unwrap();
+
+        assert_eq!(state.revision, 5);
+        assert_eq!(state.stories[&2].assignee, Some("ann".to_owned()));
+        assert_eq!(state.stories[&2].priority, Priority::High);
+        assert_eq!(state.stories[&2].story_points, Some(3));
+
+        let (priority, extra): (String, String) = db.connection.query_row("SELECT priority, extra FROM stories WHERE id = 2", [], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        let layout_version: i64 = db.connection.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+
+        assert_eq!(priority, "High");
+        assert!(!extra.contains("assignee"));
+        assert_eq!(layout_version, LAYOUT_VERSION);
+    }
+
+    #[test]
+    fn write_db_should_return_conflict_error_for_stale_revision() {
+        let db = SqliteDatabase::open_in_memory().unwrap();
+
+        db.write_db(&sample_state()).unwrap();
+        let error = db.write_db(&sample_state()).unwrap_err();
+
+        assert_eq!(error.downcast_ref::<ConflictError>(), Some(&ConflictError { expected_revision: 0, found_revision: 1 }));
+    }
+
+    #[test]
+    fn write_db_should_reject_stories_without_epic() {
+        let db = SqliteDatabase::open_in_memory().unwrap();
+
+        let mut state = sample_state();
+        state.stories.insert(4, Story::new("".to_owned(), "".to_owned()));
+
+        assert!(db.write_db(&state).is_err());
+        assert_eq!(db.read_db().unwrap().last_item_id, 0);
+    }
+
+    #[test]
+    fn stories_should_reference_existing_epic() {
+        let db = SqliteDatabase::open_in_memory().unwrap();
+
+        let result = db.connection.execute(
+            "INSERT INTO stories (id, epic_id, position, name, description, status) VALUES (1, 999, 0, '', '', 'Open')", []);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn migrate_json_to_sqlite_should_copy_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+
+        let json_state = JSONFileDatabase::new("./data/db.json".to_owned(), 0).read_db().unwrap();
+
+        assert_eq!(migrate_json_to_sqlite("./data/db.json", &sqlite_path).unwrap(), (1, 2));
+
+        let sqlite_state = SqliteDatabase::open(&sqlite_path).unwrap().read_db().unwrap();
+
+        assert_eq!(sqlite_state, DBState { revision: 1, ..json_state });
+    }
+
+    #[test]
+    fn migrate_json_to_sqlite_should_refuse_non_empty_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_owned();
+
+        migrate_json_to_sqlite("./data/db.json", &sqlite_path).unwrap();
+
+        assert!(migrate_json_to_sqlite("./data/db.json", &sqlite_path).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::models::{DBState, Epic, Story};
+
+/// The columns of the CSV export, in order. `import` reads the same layout back.
+pub const CSV_COLUMNS: [&str; 10] = ["id", "epic_id", "epic", "name", "description", "status", "priority", "story_points", "assignee", "due_date"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(anyhow!("unknown export format \"{}\", expected one of csv, markdown", s))
+        }
+    }
+}
+
+pub fn export<W: Write>(db_state: &DBState, format: ExportFormat, writer: &mut W) -> Result<()> {
+    match format {
+        ExportFormat::Csv => export_csv(db_state, writer),
+        ExportFormat::Markdown => export_markdown(db_state, writer),
+    }
+}
+
+/// Writes one row per story, epics in id order and stories in the order of their epic.
+pub fn export_csv<W: Write>(db_state: &DBState, writer: &mut W) -> Result<()> {
+    writeln!(writer, "{}", CSV_COLUMNS.join(","))?;
+
+    for (epic_id, epic, stories) in epics_with_stories(db_state) {
+        for (story_id, story) in stories {
+            let fields = [
+                story_id.to_string(),
+                epic_id.to_string(),
+                epic.name.clone(),
+                story.name.clone(),
+                story.description.clone(),
+                story.status.to_string(),
+                story.priority.to_string(),
+                story.story_points.map(|points| points.to_string()).unwrap_or_default(),
+                story.assignee.clone().unwrap_or_default(),
+                story.due_date.map(|date| date.to_string()).unwrap_or_default(),
+            ];
+
+            writeln!(writer, "{}", fields.iter().map(|field| csv_field(field)).join(","))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a section per epic, in id order, with its description and a table of its stories.
+pub fn export_markdown<W: Write>(db_state: &DBState, writer: &mut W) -> Result<()> {
+    writeln!(writer, "# Project")?;
+
+    for (epic_id, epic, stories) in epics_with_stories(db_state) {
+        writeln!(writer)?;
+        writeln!(writer, "## {} {} ({})", epic_id, markdown_line(&epic.name), epic.status)?;
+
+        if !epic.description.trim().is_empty() {
+            writeln!(writer)?;
+            writeln!(writer, "{}", markdown_text(&epic.description))?;
+        }
+
+        writeln!(writer)?;
+
+        if stories.is_empty() {
+            writeln!(writer, "_No stories._")?;
+            continue;
+        }
+
+        writeln!(writer, "| id | name | description | status | priority | points | assignee | due date |")?;
+        writeln!(writer, "|---:|---|---|---|---|---:|---|---|")?;
+
+        for (story_id, story) in stories {
+            let cells = [
+                story_id.to_string(),
+                markdown_cell(&story.name),
+                markdown_cell(&story.description),
+                story.status.to_string(),
+                story.priority.to_string(),
+                story.story_points.map_or("-".to_owned(), |points| points.to_string()),
+                story.assignee.as_deref().map_or("-".to_owned(), markdown_cell),
+                story.due_date.map_or("-".to_owned(), |date| date.to_string()),
+            ];
+
+            writeln!(writer, "| {} |", cells.join(" | "))?;
+        }
+    }
+
+    Ok(())
+}
+
+// an epic id, the epic and its stories in epic order
+type EpicSection<'a> = (u32, &'a Epic, Vec<(u32, &'a Story)>);
+
+// epics sorted by id
+fn epics_with_stories(db_state: &DBState) -> Vec<EpicSection<'_>> {
+    db_state.epics.iter()
+        .sorted_by_key(|(id, _)| **id)
+        .map(|(id, epic)| (*id, epic, epic.stories.iter().filter_map(|story_id| db_state.stories.get(story_id).map(|story| (*story_id, story))).collect()))
+        .collect()
+}
+
+/// Quotes a CSV field if it contains a comma, quote or line break, doubling the quotes inside (RFC 4180).
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+// backslash escapes for the characters Markdown would otherwise format
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Escapes `text` for a table cell. Line breaks would end the row, so they become `<br>`.
+pub fn markdown_cell(text: &str) -> String {
+    text.lines().map(escape_markdown).join("<br>")
+}
+
+// headings have to stay on one line
+fn markdown_line(text: &str) -> String {
+    text.lines().map(escape_markdown).join(" ")
+}
+
+// paragraphs keep their line breaks
+fn markdown_text(text: &str) -> String {
+    text.lines().map(escape_markdown).join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::models::{Date, Status};
+
+    use super::*;
+
+    fn sample_state() -> DBState {
+        let mut epic = Epic::new("Billing, \"v2\"".to_owned(), "Invoices\nand payments".to_owned());
+        epic.stories = vec![3, 2];
+
+        let mut story = Story::new("Print | export".to_owned(), "line one\nline \"two\", *bold*".to_owned());
+        story.status = Status::InProgress;
+        story.story_points = Some(5);
+        story.due_date = Some(Date::new(2024, 6, 14).unwrap());
+
+        DBState {
+            last_item_id: 4,
+            revision: 0,
+            epics: HashMap::from([(1, epic), (4, Epic::new("Empty".to_owned(), "".to_owned()))]),
+            stories: HashMap::from([(2, story), (3, Story::new("Login".to_owned(), "".to_owned()))]),
+            links: vec![],
+            sprints: HashMap::new()
+        }
+    }
+
+    fn export_to_string(format: ExportFormat) -> String {
+        let mut output = vec![];
+        export(&sample_state(), format, &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn csv_field_should_quote_special_characters() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn export_csv_should_write_one_row_per_story() {
+        assert_eq!(export_to_string(ExportFormat::Csv), "\
+id,epic_id,epic,name,description,status,priority,story_points,assignee,due_date
+3,1,\"Billing, \"\"v2\"\"\",Login,,OPEN,MEDIUM,,,
+2,1,\"Billing, \"\"v2\"\"\",Print | export,\"line one\nline \"\"two\"\", *bold*\",IN PROGRESS,MEDIUM,5,,2024-06-14
+");
+    }
+
+    #[test]
+    fn markdown_cell_should_escape_pipes_and_line_breaks() {
+        assert_eq!(markdown_cell("a | b"), "a \\| b");
+        assert_eq!(markdown_cell("one\r\ntwo\nthree"), "one<br>two<br>three");
+        assert_eq!(markdown_cell("*not bold* \\"), "\\*not bold\\* \\\\");
+    }
+
+    #[test]
+    fn export_markdown_should_write_section_per_epic() {
+        assert_eq!(export_to_string(ExportFormat::Markdown), "\
+# Project
+
+## 1 Billing, \"v2\" (OPEN)
+
+Invoices
+and payments
+
+| id | name | description | status | priority | points | assignee | due date |
+|---:|---|---|---|---|---:|---|---|
+| 3 | Login |  | OPEN | MEDIUM | - | - | - |
+| 2 | Print \\| export | line one<br>line \"two\", \\*bold\\* | IN PROGRESS | MEDIUM | 5 | - | 2024-06-14 |
+
+## 4 Empty (OPEN)
+
+_No stories._
+");
+    }
+
+    #[test]
+    fn export_format_should_parse_names() {
+        assert_eq!("csv".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert_eq!("md".parse::<ExportFormat>().unwrap(), ExportFormat::Markdown);
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
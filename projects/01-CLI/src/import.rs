@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::export::CSV_COLUMNS;
+use crate::models::{DBState, Epic, Story};
+
+/// Columns every import file needs, in any order. The others in `CSV_COLUMNS` are optional.
+pub const REQUIRED_COLUMNS: [&str; 3] = ["epic_id", "name", "status"];
+
+/// The epic an imported story goes to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EpicRef {
+    Existing(u32),
+    // index into `ImportPlan::epics`
+    New(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedStory {
+    pub line: usize,
+    pub epic: EpicRef,
+    pub story: Story,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RejectedRow {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Display for RejectedRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// What an import would add, before any id is assigned.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportPlan {
+    // the epic id used in the file and the epic, in the order of their first story
+    pub epics: Vec<(u32, Epic)>,
+    pub stories: Vec<ImportedStory>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+/// The ids `JiraDatabase::import` assigned, in the order of `ImportPlan::epics` and `ImportPlan::stories`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportIds {
+    pub epics: Vec<u32>,
+    pub stories: Vec<u32>,
+}
+
+/// Validates every row of a CSV in the `export` layout against `db_state`.
+///
+/// An `epic_id` that comes with an `epic` name on at least one row stands for a new epic, whatever
+/// its id in the file; any other `epic_id` must be an epic already in the database. The `id` column
+/// is ignored, imported items always get new ids.
+///
+/// Problems with the header fail the whole import, problems with a row only reject that row.
+pub fn plan_import(db_state: &DBState, input: &str) -> Result<ImportPlan> {
+    let mut records = parse_csv(input.strip_prefix('\u{feff}').unwrap_or(input))?.into_iter();
+
+    let Some((_, header)) = records.next() else {
+        return Err(anyhow!("the import file is empty"));
+    };
+
+    let columns = read_header(&header)?;
+    let rows: Vec<(usize, Row)> = records.map(|(line, fields)| (line, Row { columns: &columns, fields })).collect();
+
+    // the first name given for each epic id of the file
+    let mut new_epic_names: HashMap<u32, (usize, String)> = HashMap::new();
+
+    for (line, row) in &rows {
+        if let (Some(Ok(epic_id)), Some(name)) = (row.get("epic_id").map(parse_epic_id), row.get("epic").map(str::trim).filter(|name| !name.is_empty())) {
+            new_epic_names.entry(epic_id).or_insert_with(|| (*line, name.to_owned()));
+        }
+    }
+
+    let mut plan = ImportPlan::default();
+    // epic id of the file to index into `plan.epics`
+    let mut new_epics: HashMap<u32, usize> = HashMap::new();
+
+    for (line, row) in rows {
+        match read_story(db_state, &row, &new_epic_names) {
+            Ok((epic_id, story)) => {
+                let epic = match new_epic_names.get(&epic_id) {
+                    Some((_, name)) => EpicRef::New(*new_epics.entry(epic_id).or_insert_with(|| {
+                        plan.epics.push((epic_id, Epic::new(name.clone(), "".to_owned())));
+                        plan.epics.len() - 1
+                    })),
+                    None => EpicRef::Existing(epic_id)
+                };
+
+                plan.stories.push(ImportedStory { line, epic, story });
+            }
+            Err(problems) => plan.rejected.push(RejectedRow { line, reason: problems.join("; ") })
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Prints how many epics and stories were (or, for a dry run, would be) created and every rejected row.
+pub fn write_summary<W: Write>(plan: &ImportPlan, ids: Option<&ImportIds>, writer: &mut W) -> Result<()> {
+    match ids {
+        Some(ids) => {
+            writeln!(writer, "Imported {} epics and {} stories", ids.epics.len(), ids.stories.len())?;
+
+            for ((file_id, epic), epic_id) in plan.epics.iter().zip(&ids.epics) {
+                writeln!(writer, "  epic {} \"{}\" -> {}", file_id, epic.name, epic_id)?;
+            }
+
+            for (imported, story_id) in plan.stories.iter().zip(&ids.stories) {
+                writeln!(writer, "  line {} -> story {}", imported.line, story_id)?;
+            }
+        }
+        None => {
+            writeln!(writer, "Dry run, nothing was imported")?;
+            writeln!(writer, "Would import {} epics and {} stories", plan.epics.len(), plan.stories.len())?;
+        }
+    }
+
+    if !plan.rejected.is_empty() {
+        writeln!(writer, "Rejected {} rows:", plan.rejected.len())?;
+
+        for rejected in &plan.rejected {
+            writeln!(writer, "  {}", rejected)?;
+        }
+    }
+
+    Ok(())
+}
+
+struct Row<'a> {
+    columns: &'a [String],
+    fields: Vec<String>,
+}
+
+impl Row<'_> {
+    // None for a column the file doesn't have
+    fn get(&self, column: &str) -> Option<&str> {
+        let position = self.columns.iter().position(|c| c == column)?;
+
+        self.fields.get(position).map(String::as_str)
+    }
+
+    // an optional column that is missing or blank
+    fn get_filled(&self, column: &str) -> Option<&str> {
+        self.get(column).map(str::trim).filter(|value| !value.is_empty())
+    }
+}
+
+fn read_header(header: &[String]) -> Result<Vec<String>> {
+    let columns: Vec<String> = header.iter().map(|column| column.trim().to_lowercase()).collect();
+
+    for column in &columns {
+        if !CSV_COLUMNS.contains(&column.as_str()) {
+            return Err(anyhow!("unknown column \"{}\", expected some of {}", column, CSV_COLUMNS.join(", ")));
+        }
+    }
+
+    if let Some(column) = columns.iter().duplicates().next() {
+        return Err(anyhow!("column \"{}\" appears more than once", column));
+    }
+
+    let missing: Vec<&str> = REQUIRED_COLUMNS.into_iter().filter(|required| !columns.iter().any(|column| column == required)).collect();
+
+    if !missing.is_empty() {
+        return Err(anyhow!("missing required columns {}", missing.join(", ")));
+    }
+
+    Ok(columns)
+}
+
+// the epic id and the story of a row, or every problem found with it
+fn read_story(db_state: &DBState, row: &Row, new_epic_names: &HashMap<u32, (usize, String)>) -> Result<(u32, Story), Vec<String>> {
+    if row.fields.len() != row.columns.len() {
+        return Err(vec![format!("expected {} fields, found {}", row.columns.len(), row.fields.len())]);
+    }
+
+    let mut problems = vec![];
+
+    let epic_id = check(&mut problems, parse_epic_id(row.get("epic_id").unwrap_or_default()).and_then(|epic_id| {
+        let epic_name = row.get_filled("epic");
+
+        match new_epic_names.get(&epic_id) {
+            Some((line, name)) if epic_name.is_some_and(|epic_name| epic_name != name) => Err(anyhow!("epic {} is named \"{}\" on line {}", epic_id, name, line)),
+            Some(_) => Ok(epic_id),
+            None if db_state.epics.contains_key(&epic_id) => Ok(epic_id),
+            None => Err(anyhow!("Epic {} not found in database", epic_id))
+        }
+    }));
+
+    let name = check(&mut problems, row.get_filled("name").map(str::to_owned).ok_or_else(|| anyhow!("name cannot be empty")));
+    let status = check(&mut problems, row.get("status").unwrap_or_default().trim().parse());
+    let priority = check(&mut problems, row.get_filled("priority").map(str::parse).transpose());
+    let story_points = check(&mut problems, row.get_filled("story_points").map(|points| points.parse().map_err(|_| anyhow!("invalid story points \"{}\"", points))).transpose());
+    let due_date = check(&mut problems, row.get_filled("due_date").map(str::parse).transpose());
+
+    match (epic_id, name, status, priority, story_points, due_date) {
+        (Some(epic_id), Some(name), Some(status), Some(priority), Some(story_points), Some(due_date)) => {
+            let mut story = Story::new(name, row.get("description").unwrap_or_default().to_owned());
+            story.status = status;
+            story.priority = priority.unwrap_or_default();
+            story.story_points = story_points;
+            story.assignee = row.get_filled("assignee").map(str::to_owned);
+            story.due_date = due_date;
+
+            Ok((epic_id, story))
+        }
+        _ => Err(problems)
+    }
+}
+
+// the value if `result` is ok, otherwise its error joins `problems`
+fn check<T>(problems: &mut Vec<String>, result: Result<T>) -> Option<T> {
+    result.map_err(|error| problems.push(error.to_string())).ok()
+}
+
+fn parse_epic_id(input: &str) -> Result<u32> {
+    input.trim().parse().map_err(|_| anyhow!("invalid epic id \"{}\"", input))
+}
+
+// RFC 4180 records with the line each starts on. Blank lines are skipped and both CRLF and LF end a record.
+fn parse_csv(input: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = vec![];
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' if quoted => quoted = false,
+                '"' if field.is_empty() => quoted = true,
+                '\n' => {
+                    line += 1;
+
+                    if quoted {
+                        field.push('\n');
+                    } else {
+                        break;
+                    }
+                }
+                '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c)
+            }
+        }
+
+        if quoted {
+            return Err(anyhow!("line {}: unterminated quoted field", start_line));
+        }
+
+        fields.push(field);
+
+        if fields.len() > 1 || !fields[0].trim().is_empty() {
+            records.push((start_line, fields));
+        }
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Date, Priority, Status};
+
+    use super::*;
+
+    fn db_state() -> DBState {
+        DBState {
+            last_item_id: 3,
+            revision: 0,
+            epics: HashMap::from([(3, Epic::new("Existing".to_owned(), "".to_owned()))]),
+            stories: HashMap::new(),
+            links: vec![],
+            sprints: HashMap::new()
+        }
+    }
+
+    #[test]
+    fn parse_csv_should_handle_quotes_and_line_breaks() {
+        let records = parse_csv("a,b\r\n\"x, \"\"y\"\"\",\"two\nlines\"\n\nlast,\n").unwrap();
+
+        assert_eq!(records, vec![
+            (1, vec!["a".to_owned(), "b".to_owned()]),
+            (2, vec!["x, \"y\"".to_owned(), "two\nlines".to_owned()]),
+            (5, vec!["last".to_owned(), "".to_owned()]),
+        ]);
+    }
+
+    #[test]
+    fn parse_csv_should_error_on_unterminated_quote() {
+        assert_eq!(parse_csv("a\n\"open,b\n").unwrap_err().to_string(), "line 2: unterminated quoted field");
+    }
+
+    #[test]
+    fn plan_import_should_check_header() {
+        let db_state = db_state();
+
+        assert_eq!(plan_import(&db_state, "").unwrap_err().to_string(), "the import file is empty");
+        assert_eq!(plan_import(&db_state, "epic_id,name\n").unwrap_err().to_string(), "missing required columns status");
+        assert!(plan_import(&db_state, "epic_id,name,status,owner\n").unwrap_err().to_string().starts_with("unknown column \"owner\""));
+        assert_eq!(plan_import(&db_state, "epic_id,name,status,Name\n").unwrap_err().to_string(), "column \"name\" appears more than once");
+    }
+
+    #[test]
+    fn plan_import_should_read_export_layout() {
+        let input = "\
+id,epic_id,epic,name,description,status,priority,story_points,assignee,due_date
+7,1,Billing,Invoices,\"one, two\",in-progress,high,5, ann ,2024-06-14
+8,3,,Login,,RESOLVED,,,,
+9,1,,Refunds,,Open,,,,
+";
+        let plan = plan_import(&db_state(), input).unwrap();
+
+        assert_eq!(plan.rejected, vec![]);
+        assert_eq!(plan.epics, vec![(1, Epic::new("Billing".to_owned(), "".to_owned()))]);
+        assert_eq!(plan.stories.iter().map(|imported| (imported.line, imported.epic.clone())).collect::<Vec<_>>(),
+            vec![(2, EpicRef::New(0)), (3, EpicRef::Existing(3)), (4, EpicRef::New(0))]);
+
+        let story = &plan.stories[0].story;
+        assert_eq!(story.description, "one, two");
+        assert_eq!(story.status, Status::InProgress);
+        assert_eq!(story.priority, Priority::High);
+        assert_eq!(story.story_points, Some(5));
+        assert_eq!(story.assignee, Some("ann".to_owned()));
+        assert_eq!(story.due_date, Some(Date::new(2024, 6, 14).unwrap()));
+        assert_eq!(plan.stories[1].story.priority, Priority::Medium);
+    }
+
+    #[test]
+    fn plan_import_should_reject_invalid_rows() {
+        let input = "\
+status,name,epic_id,epic,story_points
+open,ok,3,,
+done,bad status,3,,
+open,,9,,x
+open,renamed,1,First,
+open,short row,3
+open,clash,1,Second,
+";
+        let plan = plan_import(&db_state(), input).unwrap();
+
+        assert_eq!(plan.stories.iter().map(|imported| imported.line).collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(plan.rejected.iter().map(ToString::to_string).collect::<Vec<_>>(), vec![
+            "line 3: unknown status \"done\", expected one of open, in-progress, resolved, closed".to_owned(),
+            "line 4: Epic 9 not found in database; name cannot be empty; invalid story points \"x\"".to_owned(),
+            "line 6: expected 5 fields, found 3".to_owned(),
+            "line 7: epic 1 is named \"First\" on line 5".to_owned(),
+        ]);
+    }
+
+    #[test]
+    fn write_summary_should_list_ids_and_rejected_rows() {
+        let plan = plan_import(&db_state(), "epic_id,epic,name,status\n1,Billing,Invoices,open\n2,,Lost,open\n").unwrap();
+
+        let mut output = vec![];
+        write_summary(&plan, None, &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "\
+Dry run, nothing was imported
+Would import 1 epics and 1 stories
+Rejected 1 rows:
+  line 3: Epic 2 not found in database
+");
+
+        let mut output = vec![];
+        write_summary(&plan, Some(&ImportIds { epics: vec![4], stories: vec![5] }), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "\
+Imported 1 epics and 1 stories
+  epic 1 \"Billing\" -> 4
+  line 2 -> story 5
+Rejected 1 rows:
+  line 3: Epic 2 not found in database
+");
+    }
+}
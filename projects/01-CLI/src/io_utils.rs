@@ -0,0 +1,40 @@
+use std::io::{self, BufRead};
+
+/// Reads a line from stdin, or `None` once stdin is closed (Ctrl-D, a closed pipe).
+pub fn get_user_input() -> io::Result<Option<String>> {
+    read_input(&mut io::stdin().lock())
+}
+
+/// Waits for enter. Returns `false` once stdin is closed, so callers can stop waiting.
+pub fn wait_for_key_press() -> io::Result<bool> {
+    Ok(get_user_input()?.is_some())
+}
+
+fn read_input<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut user_input = String::new();
+
+    if reader.read_line(&mut user_input)? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(user_input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_input_should_return_line() {
+        let mut reader = "1\n".as_bytes();
+
+        assert_eq!(read_input(&mut reader).unwrap(), Some("1\n".to_owned()));
+    }
+
+    #[test]
+    fn read_input_should_return_none_at_end_of_input() {
+        let mut reader = "".as_bytes();
+
+        assert_eq!(read_input(&mut reader).unwrap(), None);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::models::{DBState, LinkType, StoryLink};
+
+/// Returned when a "blocks" link would make a story wait on itself.
+#[derive(Debug, PartialEq)]
+pub struct DependencyCycleError {
+    // the existing chain of "blocks" links from the new link's target back to its source
+    pub path: Vec<u32>
+}
+
+impl Display for DependencyCycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (source, target) = (self.path[self.path.len() - 1], self.path[0]);
+
+        write!(f, "story {} cannot block story {}, that would create the cycle {} -> {}", source, target, source, self.path.iter().join(" -> "))
+    }
+}
+
+impl std::error::Error for DependencyCycleError {}
+
+/// Finds the shortest chain of "blocks" links leading from story `from` to story `to`, both included.
+pub fn blocking_path(links: &[StoryLink], from: u32, to: u32) -> Option<Vec<u32>> {
+    let mut previous: HashMap<u32, u32> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(story_id) = queue.pop_front() {
+        if story_id == to {
+            let mut path = vec![to];
+
+            while let Some(story_id) = previous.get(path.last().unwrap()) {
+                path.push(*story_id);
+            }
+
+            path.reverse();
+            return Some(path);
+        }
+
+        for link in links.iter().filter(|link| link.link_type == LinkType::Blocks && link.from == story_id) {
+            if link.to != from && !previous.contains_key(&link.to) {
+                previous.insert(link.to, story_id);
+                queue.push_back(link.to);
+            }
+        }
+    }
+
+    None
+}
+
+/// The stories that block `story_id` and are not resolved or closed yet, sorted by id.
+pub fn open_blockers(db_state: &DBState, story_id: u32) -> Vec<u32> {
+    db_state.links.iter()
+        .filter(|link| link.link_type == LinkType::Blocks && link.to == story_id)
+        .filter(|link| db_state.stories.get(&link.from).is_some_and(|story| !story.status.is_finished()))
+        .map(|link| link.from)
+        .sorted()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Status, Story};
+
+    use super::*;
+
+    fn blocks(from: u32, to: u32) -> StoryLink {
+        StoryLink { from, to, link_type: LinkType::Blocks }
+    }
+
+    #[test]
+    fn blocking_path_should_find_shortest_chain() {
+        let links = [blocks(1, 2), blocks(2, 3), blocks(3, 4), blocks(1, 4), StoryLink { from: 4, to: 5, link_type: LinkType::RelatesTo }];
+
+        assert_eq!(blocking_path(&links, 1, 4), Some(vec![1, 4]));
+        assert_eq!(blocking_path(&links, 2, 4), Some(vec![2, 3, 4]));
+        assert_eq!(blocking_path(&links, 4, 1), None);
+        assert_eq!(blocking_path(&links, 4, 5), None);
+    }
+
+    #[test]
+    fn dependency_cycle_error_should_describe_cycle() {
+        // 8 already blocks 5 through 3, so 5 can't block 8
+        let error = DependencyCycleError { path: vec![8, 3, 5] };
+
+        assert_eq!(error.to_string(), "story 5 cannot block story 8, that would create the cycle 5 -> 8 -> 3 -> 5");
+    }
+
+    #[test]
+    fn open_blockers_should_skip_finished_stories() {
+        let mut closed = Story::new("".to_owned(), "".to_owned());
+        closed.status = Status::Closed;
+
+        let db_state = DBState {
+            last_item_id: 4,
+            revision: 0,
+            epics: HashMap::new(),
+            stories: HashMap::from([(1, Story::new("".to_owned(), "".to_owned())), (2, closed), (3, Story::new("".to_owned(), "".to_owned()))]),
+            links: vec![blocks(3, 4), blocks(2, 4), blocks(1, 4), StoryLink { from: 1, to: 3, link_type: LinkType::RelatesTo }],
+            sprints: HashMap::new()
+        };
+
+        assert_eq!(open_blockers(&db_state, 4), vec![1, 3]);
+        assert!(open_blockers(&db_state, 3).is_empty());
+    }
+}
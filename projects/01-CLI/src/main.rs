@@ -1,7 +1,102 @@
+use std::rc::Rc;
+
+use anyhow::{Context, Result};
+
 mod models;
+
+mod commands;
+use commands::*;
+
+mod output;
+
 mod db;
+use db::*;
+
 mod ui;
 
+mod io_utils;
+use io_utils::*;
+
+mod navigator;
+use navigator::*;
+
+mod workflow;
+
+mod clock;
+
+mod undo;
+
+mod search;
+
+mod links;
+
+mod progress;
+
+mod export;
+
+mod import;
+
 fn main() {
-    println!("Welcome To My-Jira!");
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = parse_args(&args).and_then(|invocation| {
+        match invocation.command {
+            Some(Command::MigrateToSqlite { json_path, sqlite_path }) => migrate_to_sqlite(&json_path, &sqlite_path, &mut std::io::stdout()),
+            Some(command) => run_command(command, invocation.format, &open_database(invocation.db_path, invocation.workflow_path.as_deref())?, &mut std::io::stdout()),
+            None => run(open_database(invocation.db_path, invocation.workflow_path.as_deref())?, invocation.page_size)
+        }
+    });
+
+    if let Err(error) = result {
+        eprintln!("Error: {:#}", error);
+        std::process::exit(exit_code(&error));
+    }
+}
+
+fn run(db: JiraDatabase, page_size: usize) -> Result<()> {
+    let db = Rc::new(db);
+    let mut navigator = Navigator::new(Rc::clone(&db), page_size);
+
+    // stdin being closed ends the session like exiting from the home page
+    loop {
+        clearscreen::clear().context("failed to clear the screen")?;
+
+        let Some(page) = navigator.get_current_page() else {
+            break;
+        };
+
+        if let Err(error) = page.draw_page() {
+            println!("Error rendering page: {}\nPress any key to continue...", error);
+
+            if !wait_for_key_press()? {
+                break;
+            }
+        };
+
+        let Some(user_input) = get_user_input()? else {
+            break;
+        };
+
+        match page.handle_input(user_input.trim()) {
+            Err(error) => {
+                println!("Error getting user input: {}\nPress any key to continue...", error);
+
+                if !wait_for_key_press()? {
+                    break;
+                }
+            }
+            Ok(Some(action)) => {
+                if let Err(error) = navigator.handle_action(action) {
+                    println!("Error handling user input: {:#}\nPress any key to continue...", error);
+
+                    if !wait_for_key_press()? {
+                        break;
+                    }
+                }
+            }
+            Ok(None) => {}
+        }
+    }
+
+    Ok(())
+}
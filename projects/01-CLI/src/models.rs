@@ -1,21 +1,49 @@
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Action {
     NavigateToEpicDetail { epic_id: u32 },
     NavigateToStoryDetail { epic_id: u32, story_id: u32 },
     NavigateToPreviousPage,
+    Search,
     CreateEpic,
     UpdateEpicStatus { epic_id: u32 },
+    UpdateEpicDetails { epic_id: u32 },
+    ToggleDerivedStatus { epic_id: u32 },
     DeleteEpic { epic_id: u32 },
     CreateStory { epic_id: u32 },
     UpdateStoryStatus { story_id: u32 },
+    UpdateStoryDetails { story_id: u32 },
+    UpdateStoryPlanning { story_id: u32 },
+    MoveStory { epic_id: u32, story_id: u32 },
     DeleteStory { epic_id: u32, story_id: u32 },
+    AddComment { target: CommentTarget },
+    EditComment { target: CommentTarget },
+    DeleteComment { target: CommentTarget },
+    AddChecklistItem { story_id: u32 },
+    ToggleChecklistItem { story_id: u32 },
+    MoveChecklistItem { story_id: u32 },
+    DeleteChecklistItem { story_id: u32 },
+    LinkStory { story_id: u32 },
+    UnlinkStory { story_id: u32 },
+    NavigateToSprints,
+    NavigateToActiveSprint,
+    NavigateToSprintDetail { sprint_id: u32 },
+    CreateSprint,
+    StartSprint { sprint_id: u32 },
+    CloseSprint { sprint_id: u32 },
+    AssignStoryToSprint { story_id: u32 },
+    // epic_id is None for the board of all epics
+    NavigateToBoard { epic_id: Option<u32> },
+    MoveCard { story_id: u32, status: Status },
+    Undo,
+    Redo,
     Exit,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Status {
     Open,
     InProgress,
@@ -23,6 +51,15 @@ pub enum Status {
     Closed,
 }
 
+impl Status {
+    pub const ALL: [Status; 4] = [Status::Open, Status::InProgress, Status::Resolved, Status::Closed];
+
+    /// Resolved and closed items count as finished, e.g. when a sprint is closed.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Status::Resolved | Status::Closed)
+    }
+}
+
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
@@ -34,12 +71,186 @@ impl Display for Status {
     }
 }
 
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    // accepts the serialized names ("InProgress"), the displayed ones ("IN PROGRESS") and kebab-case ("in-progress")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "open" => Ok(Self::Open),
+            "inprogress" => Ok(Self::InProgress),
+            "resolved" => Ok(Self::Resolved),
+            "closed" => Ok(Self::Closed),
+            _ => Err(anyhow!("unknown status \"{}\", expected one of open, in-progress, resolved, closed", s))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [Priority::Low, Priority::Medium, Priority::High, Priority::Critical];
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Self::Low => write!(f, "LOW"),
+            Self::Medium => write!(f, "MEDIUM"),
+            Self::High => write!(f, "HIGH"),
+            Self::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(anyhow!("unknown priority \"{}\", expected one of low, medium, high, critical", s))
+        }
+    }
+}
+
+/// A calendar date without time zone, persisted as "YYYY-MM-DD".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> anyhow::Result<Self> {
+        let leap_year = (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap_year => 29,
+            2 => 28,
+            _ => return Err(anyhow!("invalid month {} in date", month))
+        };
+
+        if year == 0 || day == 0 || day > days_in_month {
+            return Err(anyhow!("invalid date {:04}-{:02}-{:02}", year, month, day));
+        }
+
+        Ok(Self { year, month, day })
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('-').collect();
+
+        let [year, month, day] = parts.as_slice() else {
+            return Err(anyhow!("invalid date \"{}\", expected YYYY-MM-DD", s));
+        };
+
+        let parse_error = |_| anyhow!("invalid date \"{}\", expected YYYY-MM-DD", s);
+
+        Date::new(year.parse().map_err(parse_error)?, month.parse().map_err(parse_error)?, day.parse().map_err(parse_error)?)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+
+/// Seconds since the Unix epoch, displayed in UTC.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp(pub u64);
+
+impl Timestamp {
+    pub fn date(&self) -> Date {
+        // days to civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = (self.0 / 86_400) as i64 + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u8;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as u16;
+
+        Date { year, month, day }
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds_of_day = self.0 % 86_400;
+
+        write!(f, "{} {:02}:{:02}:{:02}", self.date(), seconds_of_day / 3_600, seconds_of_day % 3_600 / 60, seconds_of_day % 60)
+    }
+}
+
+/// One entry of an item's change history. Values are stored as displayed, empty when unset.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+    pub changed_at: Timestamp,
+}
+
+// shared by Epic and Story, does nothing if the value didn't actually change
+fn record_change(history: &mut Vec<Change>, updated_at: &mut Option<Timestamp>, field: &str, old_value: String, new_value: String, now: Timestamp) {
+    if old_value == new_value {
+        return;
+    }
+
+    history.push(Change { field: field.to_owned(), old_value, new_value, changed_at: now });
+    *updated_at = Some(now);
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Epic {
     pub name: String,
     pub description: String,
     pub status: Status,
     pub stories: Vec<u32>,
+    // None for items created before timestamps were recorded
+    pub created_at: Option<Timestamp>,
+    pub updated_at: Option<Timestamp>,
+    pub history: Vec<Change>,
+    pub comments: Vec<Comment>,
+    // opt-in, the status then follows the stories, see `EpicProgress::derived_status`
+    pub derive_status: bool,
 }
 
 impl Epic {
@@ -48,9 +259,18 @@ impl Epic {
             name,
             description,
             status: Status::Open,
-            stories: vec![]
+            stories: vec![],
+            created_at: None,
+            updated_at: None,
+            history: vec![],
+            comments: vec![],
+            derive_status: false
         }
     }
+
+    pub fn record_change(&mut self, field: &str, old_value: String, new_value: String, now: Timestamp) {
+        record_change(&mut self.history, &mut self.updated_at, field, old_value, new_value, now);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -58,6 +278,17 @@ pub struct Story {
     pub name: String,
     pub description: String,
     pub status: Status,
+    pub assignee: Option<String>,
+    pub priority: Priority,
+    pub story_points: Option<u32>,
+    pub due_date: Option<Date>,
+    // None for items created before timestamps were recorded
+    pub created_at: Option<Timestamp>,
+    pub updated_at: Option<Timestamp>,
+    pub history: Vec<Change>,
+    pub comments: Vec<Comment>,
+    pub checklist: Vec<ChecklistItem>,
+    pub sprint_id: Option<u32>,
 }
 
 impl Story {
@@ -65,14 +296,316 @@ impl Story {
         Self {
             name,
             description,
-            status: Status::Open
+            status: Status::Open,
+            assignee: None,
+            priority: Priority::default(),
+            story_points: None,
+            due_date: None,
+            created_at: None,
+            updated_at: None,
+            history: vec![],
+            comments: vec![],
+            checklist: vec![],
+            sprint_id: None
+        }
+    }
+
+    pub fn record_change(&mut self, field: &str, old_value: String, new_value: String, now: Timestamp) {
+        record_change(&mut self.history, &mut self.updated_at, field, old_value, new_value, now);
+    }
+
+    pub fn planning(&self) -> StoryPlanning {
+        StoryPlanning { assignee: self.assignee.clone(), priority: self.priority.clone(), story_points: self.story_points, due_date: self.due_date }
+    }
+
+    /// Number of open checklist items.
+    pub fn open_checklist_items(&self) -> usize {
+        self.checklist.iter().filter(|item| !item.done).count()
+    }
+
+    /// The checklist progress as "n/m done", or "-" if the story has no checklist.
+    pub fn checklist_progress(&self) -> String {
+        if self.checklist.is_empty() {
+            return "-".to_owned();
         }
+
+        format!("{}/{} done", self.checklist.len() - self.open_checklist_items(), self.checklist.len())
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    // unique within the epic or story the comment belongs to
+    pub id: u32,
+    pub author: String,
+    pub body: String,
+    pub created_at: Timestamp,
+    // None until the comment is edited
+    pub edited_at: Option<Timestamp>,
+}
+
+/// The epic or story a comment belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentTarget {
+    Epic { epic_id: u32 },
+    Story { story_id: u32 },
+}
+
+impl Display for CommentTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Epic { epic_id } => write!(f, "Epic {}", epic_id),
+            Self::Story { story_id } => write!(f, "Story {}", story_id),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LinkType {
+    Blocks,
+    RelatesTo,
+}
+
+impl LinkType {
+    /// How the link reads from the story it points to, e.g. "blocked by".
+    pub fn inverse_label(&self) -> &'static str {
+        match self {
+            Self::Blocks => "blocked by",
+            Self::RelatesTo => "relates to",
+        }
+    }
+}
+
+impl Display for LinkType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blocks => write!(f, "blocks"),
+            Self::RelatesTo => write!(f, "relates to"),
+        }
+    }
+}
+
+/// A typed link between two stories, read as "`from` blocks `to`" or "`from` relates to `to`".
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoryLink {
+    pub from: u32,
+    pub to: u32,
+    pub link_type: LinkType,
+}
+
+impl StoryLink {
+    pub fn involves(&self, story_id: u32) -> bool {
+        self.from == story_id || self.to == story_id
+    }
+
+    /// How the link reads from `story_id`: its label and the story on the other end.
+    pub fn seen_from(&self, story_id: u32) -> (&'static str, u32) {
+        if self.from == story_id {
+            match self.link_type {
+                LinkType::Blocks => ("blocks", self.to),
+                LinkType::RelatesTo => ("relates to", self.to),
+            }
+        } else {
+            (self.link_type.inverse_label(), self.from)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SprintState {
+    Planned,
+    Active,
+    Closed,
+}
+
+impl Display for SprintState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Planned => write!(f, "PLANNED"),
+            Self::Active => write!(f, "ACTIVE"),
+            Self::Closed => write!(f, "CLOSED"),
+        }
+    }
+}
+
+/// A time-boxed iteration. Sprints share the id sequence of epics and stories.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sprint {
+    pub name: String,
+    pub goal: String,
+    pub start_date: Date,
+    pub end_date: Date,
+    pub state: SprintState,
+    // None for sprints created before timestamps were recorded
+    pub created_at: Option<Timestamp>,
+    pub updated_at: Option<Timestamp>,
+    pub history: Vec<Change>,
+}
+
+impl Sprint {
+    pub fn new(name: String, goal: String, start_date: Date, end_date: Date) -> Self {
+        Self { name, goal, start_date, end_date, state: SprintState::Planned, created_at: None, updated_at: None, history: vec![] }
+    }
+
+    pub fn record_change(&mut self, field: &str, old_value: String, new_value: String, now: Timestamp) {
+        record_change(&mut self.history, &mut self.updated_at, field, old_value, new_value, now);
+    }
+}
+
+/// The planning fields of a story, replaced as a whole by `JiraDatabase::update_story_planning`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StoryPlanning {
+    pub assignee: Option<String>,
+    pub priority: Priority,
+    pub story_points: Option<u32>,
+    pub due_date: Option<Date>,
+}
+
+/// A partial update of an epic's or story's details. Fields left as `None` are kept as they are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DetailsUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DBState {
     pub last_item_id: u32,
+    // bumped on every write, used to detect concurrent modifications
+    pub revision: u64,
     pub epics: HashMap<u32, Epic>,
-    pub stories: HashMap<u32, Story>
-}
\ No newline at end of file
+    pub stories: HashMap<u32, Story>,
+    pub links: Vec<StoryLink>,
+    pub sprints: HashMap<u32, Sprint>
+}
+
+impl DBState {
+    /// The comments of the epic or story `target` points at, or `None` if it doesn't exist.
+    pub fn comments(&self, target: CommentTarget) -> Option<&Vec<Comment>> {
+        match target {
+            CommentTarget::Epic { epic_id } => self.epics.get(&epic_id).map(|epic| &epic.comments),
+            CommentTarget::Story { story_id } => self.stories.get(&story_id).map(|story| &story.comments),
+        }
+    }
+
+    /// Epics, stories and sprints share one id sequence.
+    pub fn is_id_in_use(&self, id: u32) -> bool {
+        self.epics.contains_key(&id) || self.stories.contains_key(&id) || self.sprints.contains_key(&id)
+    }
+
+    /// The ids of the stories planned into `sprint_id`, sorted.
+    pub fn sprint_stories(&self, sprint_id: u32) -> Vec<u32> {
+        let mut story_ids: Vec<u32> = self.stories.iter().filter(|(_, story)| story.sprint_id == Some(sprint_id)).map(|(id, _)| *id).collect();
+        story_ids.sort();
+
+        story_ids
+    }
+
+    /// The id of the active sprint, there is at most one.
+    pub fn active_sprint_id(&self) -> Option<u32> {
+        self.sprints.iter().find(|(_, sprint)| sprint.state == SprintState::Active).map(|(id, _)| *id)
+    }
+
+    /// The id of the epic `story_id` belongs to.
+    pub fn epic_of_story(&self, story_id: u32) -> Option<u32> {
+        self.epics.iter().find(|(_, epic)| epic.stories.contains(&story_id)).map(|(id, _)| *id)
+    }
+
+    /// Records a change in the history of the epic or story `target`. Nothing happens if it doesn't exist.
+    pub fn record_change(&mut self, target: CommentTarget, field: &str, old_value: String, new_value: String, now: Timestamp) {
+        match target {
+            CommentTarget::Epic { epic_id } => if let Some(epic) = self.epics.get_mut(&epic_id) {
+                epic.record_change(field, old_value, new_value, now);
+            },
+            CommentTarget::Story { story_id } => if let Some(story) = self.stories.get_mut(&story_id) {
+                story.record_change(field, old_value, new_value, now);
+            },
+        }
+    }
+
+    pub fn comments_mut(&mut self, target: CommentTarget) -> Option<&mut Vec<Comment>> {
+        match target {
+            CommentTarget::Epic { epic_id } => self.epics.get_mut(&epic_id).map(|epic| &mut epic.comments),
+            CommentTarget::Story { story_id } => self.stories.get_mut(&story_id).map(|story| &mut story.comments),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_should_parse_and_display() {
+        let date: Date = "2024-02-29".parse().unwrap();
+
+        assert_eq!(date, Date { year: 2024, month: 2, day: 29 });
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(Date::new(987, 1, 5).unwrap().to_string(), "0987-01-05");
+    }
+
+    #[test]
+    fn date_should_reject_invalid_dates() {
+        for input in ["2023-02-29", "2024-13-01", "2024-04-31", "2024-00-10", "0-01-01", "2024-1", "2024/01/01", "tomorrow", ""] {
+            assert!(input.parse::<Date>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn date_should_serialize_as_string() {
+        let date = Date::new(2024, 3, 1).unwrap();
+
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"2024-03-01\"");
+        assert_eq!(serde_json::from_str::<Date>("\"2024-03-01\"").unwrap(), date);
+        assert!(serde_json::from_str::<Date>("\"2024-03-32\"").is_err());
+    }
+
+    #[test]
+    fn timestamp_should_display_utc_date_and_time() {
+        assert_eq!(Timestamp(0).to_string(), "1970-01-01 00:00:00");
+        assert_eq!(Timestamp(951_782_400).to_string(), "2000-02-29 00:00:00");
+        assert_eq!(Timestamp(1_719_792_000 - 1).to_string(), "2024-06-30 23:59:59");
+        assert_eq!(Timestamp(1_719_792_000).date(), Date::new(2024, 7, 1).unwrap());
+    }
+
+    #[test]
+    fn record_change_should_skip_unchanged_values() {
+        let mut story = Story::new("".to_owned(), "".to_owned());
+
+        story.record_change("status", "OPEN".to_owned(), "OPEN".to_owned(), Timestamp(10));
+        assert!(story.history.is_empty());
+        assert_eq!(story.updated_at, None);
+
+        story.record_change("status", "OPEN".to_owned(), "IN PROGRESS".to_owned(), Timestamp(20));
+        assert_eq!(story.history, vec![Change { field: "status".to_owned(), old_value: "OPEN".to_owned(), new_value: "IN PROGRESS".to_owned(), changed_at: Timestamp(20) }]);
+        assert_eq!(story.updated_at, Some(Timestamp(20)));
+    }
+
+    #[test]
+    fn priority_should_parse_ignoring_case() {
+        assert_eq!("HIGH".parse::<Priority>().unwrap(), Priority::High);
+        assert_eq!("low".parse::<Priority>().unwrap(), Priority::Low);
+        assert!("urgent".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn checklist_progress_should_count_done_items() {
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        assert_eq!(story.checklist_progress(), "-");
+
+        story.checklist = vec![
+            ChecklistItem { text: "write".to_owned(), done: true },
+            ChecklistItem { text: "review".to_owned(), done: false },
+            ChecklistItem { text: "ship".to_owned(), done: false },
+        ];
+
+        assert_eq!(story.open_checklist_items(), 2);
+        assert_eq!(story.checklist_progress(), "1/3 done");
+    }
+}
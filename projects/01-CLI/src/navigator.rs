@@ -0,0 +1,962 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Context, Ok, Result};
+use itertools::Itertools;
+
+use crate::{db::JiraDatabase, links::open_blockers, models::{Action, DBState, SprintState, Status, StoryLink}, ui::{BoardPage, EpicDetail, HomePage, Page, Prompts, SearchResults, SprintDetail, SprintsPage, StoryDetail}, undo::{Operation, UndoLog}};
+
+pub struct Navigator {
+    pages: Vec<Box<dyn Page>>,
+    prompts: Prompts,
+    db: Rc<JiraDatabase>,
+    // lives as long as the navigator, i.e. the interactive session
+    undo_log: UndoLog,
+    // number of rows per page of the epic and story lists
+    page_size: usize
+}
+
+impl Navigator {
+    pub fn new(db: Rc<JiraDatabase>, page_size: usize) -> Self {
+        Self {
+            pages: vec![Box::new(HomePage::new(Rc::clone(&db), page_size))],
+            prompts: Prompts::new(),
+            db,
+            undo_log: UndoLog::new(),
+            page_size
+        }
+    }
+
+    pub fn get_current_page(&self) -> Option<&dyn Page> {
+        self.pages.last().map(|page| page.as_ref())
+    }
+
+    pub fn handle_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::NavigateToEpicDetail { epic_id } => {
+                self.pages.push(Box::new(EpicDetail::new(epic_id, Rc::clone(&self.db), self.page_size)));
+            }
+            Action::NavigateToStoryDetail { epic_id, story_id } => {
+                self.pages.push(Box::new(StoryDetail { epic_id, story_id, db: Rc::clone(&self.db) }));
+            }
+            Action::NavigateToPreviousPage => {
+                self.pages.pop();
+            }
+            Action::Search => {
+                let query = (self.prompts.search)()?;
+
+                if !query.is_empty() {
+                    self.pages.push(Box::new(SearchResults { query, db: Rc::clone(&self.db) }));
+                }
+            }
+            Action::CreateEpic => {
+                let epic = (self.prompts.create_epic)()?;
+                self.apply(Operation::CreateEpic { epic }).with_context(|| anyhow!("failed to create epic!"))?;
+            }
+            Action::UpdateEpicStatus { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("could not find epic!"))?;
+
+                if let Some(status) = (self.prompts.update_status)(&self.db.workflow.next_statuses(&epic.status))? {
+                    self.apply(Operation::UpdateEpicStatus { epic_id, status, check_workflow: true }).with_context(|| anyhow!("failed to update epic!"))?;
+                }
+            }
+            Action::UpdateEpicDetails { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("could not find epic!"))?;
+
+                let update = (self.prompts.update_details)(&epic.name, &epic.description)?;
+                self.apply(Operation::UpdateEpic { epic_id, update }).with_context(|| anyhow!("failed to update epic!"))?;
+            }
+            Action::ToggleDerivedStatus { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("could not find epic!"))?;
+
+                self.apply(Operation::SetDerivedStatus { epic_id, derive_status: !epic.derive_status, status: None }).with_context(|| anyhow!("failed to update epic!"))?;
+            }
+            Action::DeleteEpic { epic_id } => {
+                if (self.prompts.delete_epic)()? {
+                    self.apply(Operation::DeleteEpic { epic_id }).with_context(|| anyhow!("failed to delete epic!"))?;
+                    // the epic detail page is no longer valid once the epic is gone
+                    self.pages.pop();
+                }
+            }
+            Action::CreateStory { epic_id } => {
+                let story = (self.prompts.create_story)()?;
+                self.apply(Operation::CreateStory { epic_id, story }).with_context(|| anyhow!("failed to create story!"))?;
+            }
+            Action::UpdateStoryStatus { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+                if let Some(status) = (self.prompts.update_status)(&self.db.workflow.next_statuses(&story.status))? {
+                    self.update_story_status(story_id, status)?;
+                }
+            }
+            Action::MoveCard { story_id, status } => {
+                self.update_story_status(story_id, status)?;
+            }
+            Action::UpdateStoryDetails { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+                let update = (self.prompts.update_details)(&story.name, &story.description)?;
+                self.apply(Operation::UpdateStory { story_id, update }).with_context(|| anyhow!("failed to update story!"))?;
+            }
+            Action::UpdateStoryPlanning { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+                let planning = (self.prompts.update_planning)(&story.planning())?;
+                self.apply(Operation::UpdateStoryPlanning { story_id, planning }).with_context(|| anyhow!("failed to update story!"))?;
+            }
+            Action::MoveStory { epic_id, story_id } => {
+                let db_state = self.db.read_db()?;
+                let epics: Vec<(u32, String)> = db_state.epics.iter()
+                    .filter(|(id, _)| **id != epic_id)
+                    .map(|(id, epic)| (*id, epic.name.clone()))
+                    .sorted()
+                    .collect();
+
+                if let Some(to_epic) = (self.prompts.move_story)(&epics)? {
+                    self.apply(Operation::MoveStory { story_id, from_epic: epic_id, to_epic }).with_context(|| anyhow!("failed to move story!"))?;
+                    // the story detail page still points at the old epic
+                    self.pages.pop();
+                    self.pages.push(Box::new(StoryDetail { epic_id: to_epic, story_id, db: Rc::clone(&self.db) }));
+                }
+            }
+            Action::DeleteStory { epic_id, story_id } => {
+                if (self.prompts.delete_story)()? {
+                    self.apply(Operation::DeleteStory { epic_id, story_id }).with_context(|| anyhow!("failed to delete story!"))?;
+                    // the story detail page is no longer valid once the story is gone
+                    self.pages.pop();
+                }
+            }
+            Action::AddComment { target } => {
+                if let Some((author, body)) = (self.prompts.add_comment)()? {
+                    self.apply(Operation::AddComment { target, author, body }).with_context(|| anyhow!("failed to add comment!"))?;
+                }
+            }
+            Action::EditComment { target } => {
+                let db_state = self.db.read_db()?;
+                let comments = db_state.comments(target).ok_or_else(|| anyhow!("could not find {}!", target.to_string().to_lowercase()))?;
+
+                if let Some(comment) = (self.prompts.choose_comment)(comments)?.and_then(|comment_id| comments.iter().find(|comment| comment.id == comment_id)) {
+                    if let Some(body) = (self.prompts.edit_comment)(&comment.body)? {
+                        self.apply(Operation::UpdateComment { target, comment_id: comment.id, body }).with_context(|| anyhow!("failed to edit comment!"))?;
+                    }
+                }
+            }
+            Action::DeleteComment { target } => {
+                let db_state = self.db.read_db()?;
+                let comments = db_state.comments(target).ok_or_else(|| anyhow!("could not find {}!", target.to_string().to_lowercase()))?;
+
+                if let Some(comment_id) = (self.prompts.choose_comment)(comments)? {
+                    self.apply(Operation::DeleteComment { target, comment_id }).with_context(|| anyhow!("failed to delete comment!"))?;
+                }
+            }
+            Action::AddChecklistItem { story_id } => {
+                if let Some(text) = (self.prompts.add_checklist_item)()? {
+                    self.apply(Operation::AddChecklistItem { story_id, text }).with_context(|| anyhow!("failed to add checklist item!"))?;
+                }
+            }
+            Action::ToggleChecklistItem { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+                if let Some(position) = (self.prompts.choose_checklist_item)(&story.checklist)? {
+                    self.apply(Operation::ToggleChecklistItem { story_id, position }).with_context(|| anyhow!("failed to update checklist item!"))?;
+                }
+            }
+            Action::MoveChecklistItem { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+                if let Some(from) = (self.prompts.choose_checklist_item)(&story.checklist)? {
+                    if let Some(to) = (self.prompts.move_checklist_item)(story.checklist.len())? {
+                        self.apply(Operation::MoveChecklistItem { story_id, from, to }).with_context(|| anyhow!("failed to move checklist item!"))?;
+                    }
+                }
+            }
+            Action::DeleteChecklistItem { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+                if let Some(position) = (self.prompts.choose_checklist_item)(&story.checklist)? {
+                    self.apply(Operation::DeleteChecklistItem { story_id, position }).with_context(|| anyhow!("failed to delete checklist item!"))?;
+                }
+            }
+            Action::LinkStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let stories: Vec<(u32, String)> = db_state.stories.iter()
+                    .filter(|(id, _)| **id != story_id)
+                    .map(|(id, story)| (*id, story.name.clone()))
+                    .sorted()
+                    .collect();
+
+                if let Some(link) = (self.prompts.link_story)(story_id, &stories)? {
+                    self.apply(Operation::LinkStories { link }).with_context(|| anyhow!("failed to link stories!"))?;
+                }
+            }
+            Action::UnlinkStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let links: Vec<StoryLink> = db_state.links.into_iter().filter(|link| link.involves(story_id)).collect();
+
+                if let Some(link) = (self.prompts.unlink_story)(story_id, &links)? {
+                    self.apply(Operation::UnlinkStories { link }).with_context(|| anyhow!("failed to remove link!"))?;
+                }
+            }
+            Action::NavigateToBoard { epic_id } => {
+                self.pages.push(Box::new(BoardPage::new(epic_id, Rc::clone(&self.db))));
+            }
+            Action::NavigateToSprints => {
+                self.pages.push(Box::new(SprintsPage { db: Rc::clone(&self.db) }));
+            }
+            Action::NavigateToActiveSprint => {
+                let sprint_id = self.db.read_db()?.active_sprint_id().ok_or_else(|| anyhow!("there is no active sprint!"))?;
+                self.pages.push(Box::new(SprintDetail { sprint_id, db: Rc::clone(&self.db) }));
+            }
+            Action::NavigateToSprintDetail { sprint_id } => {
+                self.pages.push(Box::new(SprintDetail { sprint_id, db: Rc::clone(&self.db) }));
+            }
+            Action::CreateSprint => {
+                let sprint = (self.prompts.create_sprint)()?;
+                self.apply(Operation::CreateSprint { sprint }).with_context(|| anyhow!("failed to create sprint!"))?;
+            }
+            Action::StartSprint { sprint_id } => {
+                self.apply(Operation::StartSprint { sprint_id }).with_context(|| anyhow!("failed to start sprint!"))?;
+            }
+            Action::CloseSprint { sprint_id } => {
+                let db_state = self.db.read_db()?;
+                let unfinished = db_state.sprint_stories(sprint_id).iter().filter(|id| !db_state.stories[id].status.is_finished()).count();
+                let sprints = sprint_choices(&db_state, |state| *state == SprintState::Planned);
+
+                if let Some(next_sprint_id) = (self.prompts.close_sprint)(unfinished, &sprints)? {
+                    self.apply(Operation::CloseSprint { sprint_id, next_sprint_id }).with_context(|| anyhow!("failed to close sprint!"))?;
+                }
+            }
+            Action::AssignStoryToSprint { story_id } => {
+                let db_state = self.db.read_db()?;
+                let sprints = sprint_choices(&db_state, |state| *state != SprintState::Closed);
+
+                if let Some(sprint_id) = (self.prompts.assign_sprint)(&sprints)? {
+                    self.apply(Operation::AssignStoryToSprint { story_id, sprint_id, check_state: true }).with_context(|| anyhow!("failed to plan story!"))?;
+                }
+            }
+            Action::Undo => {
+                self.undo_log.undo(&self.db)?;
+                self.drop_stale_pages()?;
+            }
+            Action::Redo => {
+                self.undo_log.redo(&self.db)?;
+                self.drop_stale_pages()?;
+            }
+            Action::Exit => {
+                self.pages.clear();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update_story_status(&mut self, story_id: u32, status: Status) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("could not find story!"))?;
+
+        // resolving a story with unfinished checklist items is allowed, but only on purpose
+        if status == Status::Resolved && story.open_checklist_items() > 0 && !(self.prompts.resolve_with_open_items)(story.open_checklist_items())? {
+            return Ok(());
+        }
+
+        // same for closing a story that is still waiting on others
+        let blockers = open_blockers(&db_state, story_id);
+
+        if status == Status::Closed && !blockers.is_empty() && !(self.prompts.close_with_open_blockers)(&blockers)? {
+            return Ok(());
+        }
+
+        self.apply(Operation::UpdateStoryStatus { story_id, status, check_workflow: true }).with_context(|| anyhow!("failed to update story!"))
+    }
+
+    fn apply(&mut self, operation: Operation) -> Result<()> {
+        self.undo_log.apply(&self.db, operation)
+    }
+
+    // undo and redo can remove or move the item a detail page shows, so drop those pages
+    // wherever they are in the stack, not only on top, or going back would land on them
+    fn drop_stale_pages(&mut self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+
+        self.pages.retain(|page| !is_stale(page.as_ref() as &dyn Any, &db_state));
+
+        Ok(())
+    }
+
+    // Private functions used for testing
+
+    #[cfg(test)]
+    fn get_page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    #[cfg(test)]
+    fn set_prompts(&mut self, prompts: Prompts) {
+        self.prompts = prompts;
+    }
+}
+
+// whether a page shows an item that is no longer where the page expects it
+fn is_stale(page: &dyn Any, db_state: &DBState) -> bool {
+    if let Some(epic_detail) = page.downcast_ref::<EpicDetail>() {
+        !db_state.epics.contains_key(&epic_detail.epic_id)
+    } else if let Some(story_detail) = page.downcast_ref::<StoryDetail>() {
+        !db_state.epics.get(&story_detail.epic_id).is_some_and(|epic| epic.stories.contains(&story_detail.story_id))
+    } else if let Some(board) = page.downcast_ref::<BoardPage>() {
+        board.epic_id.is_some_and(|epic_id| !db_state.epics.contains_key(&epic_id))
+    } else if let Some(sprint_detail) = page.downcast_ref::<SprintDetail>() {
+        !db_state.sprints.contains_key(&sprint_detail.sprint_id)
+    } else {
+        false
+    }
+}
+
+// the (id, "name (start - end)") of the sprints in an accepted state, in the order they start
+fn sprint_choices(db_state: &DBState, accepts: impl Fn(&SprintState) -> bool) -> Vec<(u32, String)> {
+    db_state.sprints.iter()
+        .filter(|(_, sprint)| accepts(&sprint.state))
+        .sorted_by_key(|(id, sprint)| (sprint.start_date, **id))
+        .map(|(id, sprint)| (*id, format!("{} ({} - {})", sprint.name, sprint.start_date, sprint.end_date)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{db::test_utils::MockDB, ui::DEFAULT_PAGE_SIZE, models::{CommentTarget, Date, DetailsUpdate, Epic, LinkType, Priority, Sprint, Story, StoryPlanning}};
+    use super::*;
+
+    fn navigator_with_mock_db() -> (Navigator, Rc<JiraDatabase>) {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        (Navigator::new(Rc::clone(&db), DEFAULT_PAGE_SIZE), db)
+    }
+
+    #[test]
+    fn should_start_on_home_page() {
+        let (nav, _) = navigator_with_mock_db();
+
+        assert_eq!(nav.get_page_count(), 1);
+
+        let current_page = nav.get_current_page().unwrap();
+        let home_page = (current_page as &dyn Any).downcast_ref::<HomePage>();
+
+        assert!(home_page.is_some());
+    }
+
+    #[test]
+    fn handle_action_should_navigate_pages() {
+        let (mut nav, _) = navigator_with_mock_db();
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 }).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let epic_detail_page = (current_page as &dyn Any).downcast_ref::<EpicDetail>();
+        assert!(epic_detail_page.is_some());
+
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id: 1, story_id: 2 }).unwrap();
+        assert_eq!(nav.get_page_count(), 3);
+
+        let current_page = nav.get_current_page().unwrap();
+        let story_detail_page = (current_page as &dyn Any).downcast_ref::<StoryDetail>();
+        assert!(story_detail_page.is_some());
+
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert_eq!(nav.get_page_count(), 1);
+
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert_eq!(nav.get_page_count(), 0);
+
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert_eq!(nav.get_page_count(), 0);
+    }
+
+    #[test]
+    fn handle_action_should_clear_pages_on_exit() {
+        let (mut nav, _) = navigator_with_mock_db();
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 }).unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id: 1, story_id: 2 }).unwrap();
+        nav.handle_action(Action::Exit).unwrap();
+
+        assert_eq!(nav.get_page_count(), 0);
+    }
+
+    #[test]
+    fn handle_action_should_handle_create_epic() {
+        let (mut nav, db) = navigator_with_mock_db();
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Ok(Epic::new("name".to_owned(), "description".to_owned())));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.len(), 1);
+
+        let epic = db_state.epics.into_values().next().unwrap();
+        assert_eq!(epic.name, "name".to_owned());
+        assert_eq!(epic.description, "description".to_owned());
+    }
+
+    #[test]
+    fn handle_action_should_handle_update_epic() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::InProgress)));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateEpicStatus { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().status, Status::InProgress);
+    }
+
+    #[test]
+    fn handle_action_should_handle_delete_epic() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.delete_epic = Box::new(|| Ok(true));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.len(), 0);
+        assert_eq!(nav.get_page_count(), 1);
+    }
+
+    #[test]
+    fn handle_action_should_not_delete_epic_without_confirmation() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.delete_epic = Box::new(|| Ok(false));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.len(), 1);
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_handle_create_story() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.create_story = Box::new(|| Ok(Story::new("name".to_owned(), "description".to_owned())));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateStory { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories.len(), 1);
+
+        let story = db_state.stories.into_values().next().unwrap();
+        assert_eq!(story.name, "name".to_owned());
+        assert_eq!(story.description, "description".to_owned());
+    }
+
+    #[test]
+    fn handle_action_should_handle_update_story() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::InProgress)));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories.get(&story_id).unwrap().status, Status::InProgress);
+    }
+
+    #[test]
+    fn handle_action_should_handle_delete_story() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.delete_story = Box::new(|| Ok(true));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id }).unwrap();
+        nav.handle_action(Action::DeleteStory { epic_id, story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories.len(), 0);
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_return_error_on_invalid_epic() {
+        let (mut nav, _) = navigator_with_mock_db();
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::Closed)));
+        nav.set_prompts(prompts);
+
+        assert!(nav.handle_action(Action::UpdateEpicStatus { epic_id: 999 }).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_only_offer_legal_statuses() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|options| {
+            assert_eq!(options, &[Status::InProgress]);
+            Ok(None)
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateEpicStatus { epic_id }).unwrap();
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+    }
+
+    #[test]
+    fn handle_action_should_handle_update_epic_details() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("nmae".to_owned(), "description".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_details = Box::new(|name, _| {
+            assert_eq!(name, "nmae");
+            Ok(DetailsUpdate { name: Some("name".to_owned()), description: None })
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateEpicDetails { epic_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().name, "name");
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().description, "description");
+    }
+
+    #[test]
+    fn handle_action_should_handle_update_story_details() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("name".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_details = Box::new(|_, _| Ok(DetailsUpdate { name: None, description: Some("description".to_owned()) }));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryDetails { story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories.get(&story_id).unwrap().description, "description");
+    }
+
+    #[test]
+    fn handle_action_should_handle_move_story() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let first_epic_id = db.create_epic(Epic::new("first".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), first_epic_id).unwrap();
+        let second_epic_id = db.create_epic(Epic::new("second".to_owned(), "".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.move_story = Box::new(move |epics| {
+            assert_eq!(epics, &[(second_epic_id, "second".to_owned())]);
+            Ok(Some(second_epic_id))
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: first_epic_id }).unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id: first_epic_id, story_id }).unwrap();
+        nav.handle_action(Action::MoveStory { epic_id: first_epic_id, story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&second_epic_id).unwrap().stories, vec![story_id]);
+        assert_eq!(nav.get_page_count(), 3);
+
+        let current_page = nav.get_current_page().unwrap();
+        let story_detail_page = (current_page as &dyn Any).downcast_ref::<StoryDetail>().unwrap();
+        assert_eq!(story_detail_page.epic_id, second_epic_id);
+    }
+
+    #[test]
+    fn handle_action_should_handle_comments() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let target = CommentTarget::Epic { epic_id };
+
+        let mut prompts = Prompts::new();
+        prompts.add_comment = Box::new(|| Ok(Some(("ana".to_owned(), "first".to_owned()))));
+        prompts.choose_comment = Box::new(|comments| Ok(comments.last().map(|comment| comment.id)));
+        prompts.edit_comment = Box::new(|body| Ok(Some(format!("{} (edited)", body))));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddComment { target }).unwrap();
+        nav.handle_action(Action::AddComment { target }).unwrap();
+        nav.handle_action(Action::EditComment { target }).unwrap();
+
+        let comments = db.read_db().unwrap().epics[&epic_id].comments.clone();
+        assert_eq!(comments.iter().map(|comment| comment.body.as_str()).collect::<Vec<_>>(), vec!["first", "first (edited)"]);
+        assert_eq!(comments[0].author, "ana");
+
+        nav.handle_action(Action::DeleteComment { target }).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].comments, comments[..1]);
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].comments, comments);
+    }
+
+    #[test]
+    fn handle_action_should_handle_checklist() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.add_checklist_item(story_id, "write".to_owned()).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.add_checklist_item = Box::new(|| Ok(Some("review".to_owned())));
+        prompts.choose_checklist_item = Box::new(|checklist| Ok(Some(checklist.len() - 1)));
+        prompts.move_checklist_item = Box::new(|_| Ok(Some(0)));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::AddChecklistItem { story_id }).unwrap();
+        nav.handle_action(Action::ToggleChecklistItem { story_id }).unwrap();
+        nav.handle_action(Action::MoveChecklistItem { story_id }).unwrap();
+
+        let checklist = db.read_db().unwrap().stories[&story_id].checklist.clone();
+        assert_eq!(checklist.iter().map(|item| (item.text.as_str(), item.done)).collect::<Vec<_>>(), vec![("review", true), ("write", false)]);
+
+        nav.handle_action(Action::DeleteChecklistItem { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].checklist, checklist[..1]);
+    }
+
+    #[test]
+    fn handle_action_should_warn_before_resolving_story_with_open_items() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.update_story_status(story_id, Status::InProgress).unwrap();
+        db.add_checklist_item(story_id, "write".to_owned()).unwrap();
+        db.add_checklist_item(story_id, "review".to_owned()).unwrap();
+        db.toggle_checklist_item(story_id, 0).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::Resolved)));
+        prompts.resolve_with_open_items = Box::new(|open_items| {
+            assert_eq!(open_items, 1);
+            Ok(false)
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].status, Status::InProgress);
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::Resolved)));
+        prompts.resolve_with_open_items = Box::new(|_| Ok(true));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].status, Status::Resolved);
+    }
+
+    #[test]
+    fn handle_action_should_handle_links() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let first_story_id = db.create_story(Story::new("first".to_owned(), "".to_owned()), epic_id).unwrap();
+        let second_story_id = db.create_story(Story::new("second".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.link_story = Box::new(move |story_id, stories| {
+            assert_eq!(stories, &[(second_story_id, "second".to_owned())]);
+            Ok(Some(StoryLink { from: story_id, to: second_story_id, link_type: LinkType::Blocks }))
+        });
+        prompts.unlink_story = Box::new(|_, links| Ok(links.first().cloned()));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::LinkStory { story_id: first_story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().links, vec![StoryLink { from: first_story_id, to: second_story_id, link_type: LinkType::Blocks }]);
+
+        nav.handle_action(Action::UnlinkStory { story_id: second_story_id }).unwrap();
+        assert!(db.read_db().unwrap().links.is_empty());
+    }
+
+    #[test]
+    fn handle_action_should_warn_before_closing_story_with_open_blockers() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let blocker_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.link_stories(StoryLink { from: blocker_id, to: story_id, link_type: LinkType::Blocks }).unwrap();
+        db.restore_story_status(story_id, Status::Resolved).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::Closed)));
+        prompts.close_with_open_blockers = Box::new(move |blockers| {
+            assert_eq!(blockers, &[blocker_id]);
+            Ok(false)
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].status, Status::Resolved);
+
+        // no warning once the blocker is done
+        db.restore_story_status(blocker_id, Status::Resolved).unwrap();
+
+        nav.handle_action(Action::UpdateStoryStatus { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].status, Status::Closed);
+    }
+
+    #[test]
+    fn handle_action_should_handle_update_story_planning() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.update_planning = Box::new(|planning| Ok(StoryPlanning { priority: Priority::Critical, story_points: Some(8), ..planning.clone() }));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::UpdateStoryPlanning { story_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+        assert_eq!(story.priority, Priority::Critical);
+        assert_eq!(story.story_points, Some(8));
+        assert_eq!(story.assignee, None);
+    }
+
+    #[test]
+    fn handle_action_should_undo_and_redo_delete_epic() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.delete_epic = Box::new(|| Ok(true));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
+        nav.handle_action(Action::Undo).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().stories, vec![story_id]);
+        assert_eq!(db_state.stories.get(&story_id).unwrap().name, "story");
+
+        nav.handle_action(Action::Redo).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+        assert!(nav.handle_action(Action::Redo).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_leave_pages_of_items_removed_by_undo() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.create_story = Box::new(|| Ok(Story::new("story".to_owned(), "".to_owned())));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::CreateStory { epic_id }).unwrap();
+
+        let story_id = db.read_db().unwrap().last_item_id;
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id }).unwrap();
+        nav.handle_action(Action::Undo).unwrap();
+
+        assert!(db.read_db().unwrap().stories.is_empty());
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_leave_stale_pages_below_the_current_one() {
+        let (mut nav, db) = navigator_with_mock_db();
+
+        let mut prompts = Prompts::new();
+        prompts.create_epic = Box::new(|| Ok(Epic::new("epic".to_owned(), "".to_owned())));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CreateEpic).unwrap();
+
+        let epic_id = db.read_db().unwrap().last_item_id;
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id }).unwrap();
+        nav.handle_action(Action::NavigateToBoard { epic_id: None }).unwrap();
+        assert_eq!(nav.get_page_count(), 3);
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+        assert!((nav.get_current_page().unwrap() as &dyn Any).downcast_ref::<BoardPage>().is_some());
+
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert!((nav.get_current_page().unwrap() as &dyn Any).downcast_ref::<HomePage>().is_some());
+    }
+
+    #[test]
+    fn handle_action_should_open_search_results() {
+        let (mut nav, _) = navigator_with_mock_db();
+
+        let mut prompts = Prompts::new();
+        prompts.search = Box::new(|| Ok("login".to_owned()));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::Search).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let search_results = (current_page as &dyn Any).downcast_ref::<SearchResults>().unwrap();
+        assert_eq!(search_results.query, "login");
+
+        let mut prompts = Prompts::new();
+        prompts.search = Box::new(|| Ok("".to_owned()));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::Search).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_pass_page_size_to_epic_detail() {
+        let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(Rc::clone(&db), 25);
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 }).unwrap();
+
+        let current_page = nav.get_current_page().unwrap();
+        let epic_detail_page = (current_page as &dyn Any).downcast_ref::<EpicDetail>().unwrap();
+        assert_eq!(epic_detail_page.list.borrow().page_size, 25);
+    }
+
+    #[test]
+    fn handle_action_should_handle_sprints() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        let mut prompts = Prompts::new();
+        prompts.create_sprint = Box::new(|| Ok(Sprint::new("sprint".to_owned(), "".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap())));
+        prompts.assign_sprint = Box::new(|sprints| Ok(Some(Some(sprints[0].0))));
+        nav.set_prompts(prompts);
+
+        assert_eq!(nav.handle_action(Action::NavigateToActiveSprint).unwrap_err().to_string(), "there is no active sprint!");
+
+        nav.handle_action(Action::CreateSprint).unwrap();
+        nav.handle_action(Action::CreateSprint).unwrap();
+        nav.handle_action(Action::AssignStoryToSprint { story_id }).unwrap();
+
+        let sprint_id = db.read_db().unwrap().stories[&story_id].sprint_id.unwrap();
+        nav.handle_action(Action::StartSprint { sprint_id }).unwrap();
+        nav.handle_action(Action::NavigateToActiveSprint).unwrap();
+
+        let current_page = nav.get_current_page().unwrap();
+        assert_eq!((current_page as &dyn Any).downcast_ref::<SprintDetail>().unwrap().sprint_id, sprint_id);
+
+        // only the other, planned sprint is offered for the unfinished story
+        let mut prompts = Prompts::new();
+        prompts.close_sprint = Box::new(|unfinished, sprints| {
+            assert_eq!(unfinished, 1);
+            assert_eq!(sprints.len(), 1);
+            Ok(Some(Some(sprints[0].0)))
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::CloseSprint { sprint_id }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.sprints[&sprint_id].state, SprintState::Closed);
+        assert_ne!(db_state.stories[&story_id].sprint_id, Some(sprint_id));
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].sprint_id, Some(sprint_id));
+    }
+
+    #[test]
+    fn handle_action_should_leave_sprint_detail_of_sprint_removed_by_undo() {
+        let (mut nav, _) = navigator_with_mock_db();
+
+        let mut prompts = Prompts::new();
+        prompts.create_sprint = Box::new(|| Ok(Sprint::new("sprint".to_owned(), "".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap())));
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::NavigateToSprints).unwrap();
+        nav.handle_action(Action::CreateSprint).unwrap();
+        nav.handle_action(Action::NavigateToSprintDetail { sprint_id: 1 }).unwrap();
+        assert_eq!(nav.get_page_count(), 3);
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+        assert!((nav.get_current_page().unwrap() as &dyn Any).downcast_ref::<SprintsPage>().is_some());
+    }
+
+    #[test]
+    fn handle_action_should_move_cards_on_the_board() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        nav.handle_action(Action::NavigateToBoard { epic_id: Some(epic_id) }).unwrap();
+        assert!((nav.get_current_page().unwrap() as &dyn Any).downcast_ref::<BoardPage>().is_some());
+
+        nav.handle_action(Action::MoveCard { story_id, status: Status::InProgress }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].status, Status::InProgress);
+
+        // the board goes through the workflow like any other status change
+        let error = nav.handle_action(Action::MoveCard { story_id, status: Status::Closed }).unwrap_err();
+        assert!(format!("{:#}", error).contains("failed to update story!"));
+
+        nav.handle_action(Action::Undo).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].status, Status::Open);
+    }
+
+    #[test]
+    fn handle_action_should_toggle_derived_status() {
+        let (mut nav, db) = navigator_with_mock_db();
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        nav.handle_action(Action::ToggleDerivedStatus { epic_id }).unwrap();
+        nav.handle_action(Action::MoveCard { story_id, status: Status::InProgress }).unwrap();
+
+        let epic = db.read_db().unwrap().epics[&epic_id].clone();
+        assert!(epic.derive_status);
+        assert_eq!(epic.status, Status::InProgress);
+
+        // a derived status can't be set by hand
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Ok(Some(Status::Resolved)));
+        nav.set_prompts(prompts);
+
+        assert!(nav.handle_action(Action::UpdateEpicStatus { epic_id }).is_err());
+
+        nav.handle_action(Action::ToggleDerivedStatus { epic_id }).unwrap();
+        assert!(!db.read_db().unwrap().epics[&epic_id].derive_status);
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].status, Status::InProgress);
+    }
+}
@@ -0,0 +1,317 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::models::{DBState, Date, Epic, Priority, Status, Story};
+use crate::ui::{fit_columns, get_header_string, get_row_string};
+
+// id, name, status and story count
+const EPIC_LIST_COLUMNS: [usize; 4] = [11, 32, 17, 8];
+// id, name and status
+const STORY_LIST_COLUMNS: [usize; 3] = [11, 32, 17];
+// id, name, description, status and story count
+const EPIC_COLUMNS: [usize; 5] = [5, 12, 27, 13, 8];
+// id, epic, name, description and status
+const STORY_COLUMNS: [usize; 5] = [5, 5, 12, 27, 13];
+// assignee, priority, points and due date, like on the story page
+const PLANNING_COLUMNS: [usize; 4] = [13, 10, 8, 12];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    // tab separated, one item per line
+    #[default]
+    Plain,
+    Json,
+    Table,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "json" => Ok(Self::Json),
+            "table" => Ok(Self::Table),
+            _ => Err(anyhow!("unknown format \"{}\", expected one of plain, json, table", s))
+        }
+    }
+}
+
+// The JSON views below are the stable, documented output schema. Add fields, never rename or remove them.
+
+#[derive(Debug, Serialize)]
+pub struct EpicView<'a> {
+    pub id: u32,
+    pub name: &'a str,
+    pub description: &'a str,
+    pub status: &'a Status,
+    pub story_ids: Vec<u32>,
+    pub story_count: usize,
+}
+
+impl<'a> EpicView<'a> {
+    pub fn new(id: u32, epic: &'a Epic) -> Self {
+        let mut story_ids = epic.stories.clone();
+        story_ids.sort();
+
+        Self { id, name: &epic.name, description: &epic.description, status: &epic.status, story_count: story_ids.len(), story_ids }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoryView<'a> {
+    pub id: u32,
+    pub epic_id: u32,
+    pub name: &'a str,
+    pub description: &'a str,
+    pub status: &'a Status,
+    pub assignee: Option<&'a str>,
+    pub priority: &'a Priority,
+    pub story_points: Option<u32>,
+    pub due_date: Option<Date>,
+}
+
+impl<'a> StoryView<'a> {
+    pub fn new(id: u32, epic_id: u32, story: &'a Story) -> Self {
+        Self {
+            id,
+            epic_id,
+            name: &story.name,
+            description: &story.description,
+            status: &story.status,
+            assignee: story.assignee.as_deref(),
+            priority: &story.priority,
+            story_points: story.story_points,
+            due_date: story.due_date
+        }
+    }
+}
+
+pub fn epic_views(db_state: &DBState) -> Vec<EpicView<'_>> {
+    let mut views: Vec<EpicView> = db_state.epics.iter().map(|(id, epic)| EpicView::new(*id, epic)).collect();
+    views.sort_by_key(|view| view.id);
+
+    views
+}
+
+pub fn story_views(db_state: &DBState, epic_id: u32) -> Result<Vec<StoryView<'_>>> {
+    let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?;
+
+    let mut views = vec![];
+
+    for id in &epic.stories {
+        let story = db_state.stories.get(id).ok_or_else(|| anyhow!("Story {} not found in database", id))?;
+        views.push(StoryView::new(*id, epic_id, story));
+    }
+
+    views.sort_by_key(|view| view.id);
+
+    Ok(views)
+}
+
+pub fn write_epic_list<W: Write>(epics: &[EpicView], format: OutputFormat, writer: &mut W) -> Result<()> {
+    match format {
+        OutputFormat::Plain => {
+            for epic in epics {
+                writeln!(writer, "{}\t{}\t{}", epic.id, epic.status, epic.name)?;
+            }
+        }
+        OutputFormat::Json => writeln!(writer, "{}", serde_json::to_string_pretty(epics)?)?,
+        OutputFormat::Table => {
+            let widths = fit_columns(&EPIC_LIST_COLUMNS);
+
+            writeln!(writer, "{}", get_header_string(&["id", "name", "status", "stories"], &widths))?;
+
+            for epic in epics {
+                writeln!(writer, "{}", get_row_string(&[&epic.id.to_string(), epic.name, &epic.status.to_string(), &epic.story_count.to_string()], &widths))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_story_list<W: Write>(stories: &[StoryView], format: OutputFormat, writer: &mut W) -> Result<()> {
+    match format {
+        OutputFormat::Plain => {
+            for story in stories {
+                writeln!(writer, "{}\t{}\t{}", story.id, story.status, story.name)?;
+            }
+        }
+        OutputFormat::Json => writeln!(writer, "{}", serde_json::to_string_pretty(stories)?)?,
+        OutputFormat::Table => {
+            let widths = fit_columns(&STORY_LIST_COLUMNS);
+
+            writeln!(writer, "{}", get_header_string(&["id", "name", "status"], &widths))?;
+
+            for story in stories {
+                writeln!(writer, "{}", get_row_string(&[&story.id.to_string(), story.name, &story.status.to_string()], &widths))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_epic<W: Write>(epic: &EpicView, format: OutputFormat, writer: &mut W) -> Result<()> {
+    match format {
+        OutputFormat::Plain => writeln!(writer, "id: {}\nname: {}\ndescription: {}\nstatus: {}\nstories: {}",
+            epic.id, epic.name, epic.description, epic.status, epic.story_count)?,
+        OutputFormat::Json => writeln!(writer, "{}", serde_json::to_string_pretty(epic)?)?,
+        OutputFormat::Table => {
+            let widths = fit_columns(&EPIC_COLUMNS);
+
+            writeln!(writer, "{}", get_header_string(&["id", "name", "description", "status", "stories"], &widths))?;
+            writeln!(writer, "{}", get_row_string(&[&epic.id.to_string(), epic.name, epic.description, &epic.status.to_string(), &epic.story_count.to_string()], &widths))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_story<W: Write>(story: &StoryView, format: OutputFormat, writer: &mut W) -> Result<()> {
+    match format {
+        OutputFormat::Plain => writeln!(writer, "id: {}\nepic: {}\nname: {}\ndescription: {}\nstatus: {}\nassignee: {}\npriority: {}\nstory points: {}\ndue date: {}",
+            story.id, story.epic_id, story.name, story.description, story.status, story.assignee.unwrap_or("-"), story.priority,
+            format_optional(story.story_points), format_optional(story.due_date))?,
+        OutputFormat::Json => writeln!(writer, "{}", serde_json::to_string_pretty(story)?)?,
+        OutputFormat::Table => {
+            let widths = fit_columns(&STORY_COLUMNS);
+
+            writeln!(writer, "{}", get_header_string(&["id", "epic", "name", "description", "status"], &widths))?;
+            writeln!(writer, "{}", get_row_string(&[&story.id.to_string(), &story.epic_id.to_string(), story.name, story.description, &story.status.to_string()], &widths))?;
+
+            let widths = fit_columns(&PLANNING_COLUMNS);
+
+            writeln!(writer)?;
+            writeln!(writer, "{}", get_header_string(&["assignee", "priority", "points", "due date"], &widths))?;
+            writeln!(writer, "{}", get_row_string(&[story.assignee.unwrap_or("-"), &story.priority.to_string(),
+                &format_optional(story.story_points), &format_optional(story.due_date)], &widths))?;
+        }
+    }
+
+    Ok(())
+}
+
+// unset planning fields are shown as "-"
+fn format_optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or("-".to_owned(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn sample_state() -> DBState {
+        let mut epic = Epic::new("epic".to_owned(), "epic description".to_owned());
+        epic.stories = vec![3, 2];
+
+        let mut story = Story::new("story".to_owned(), "story description".to_owned());
+        story.status = Status::InProgress;
+        story.assignee = Some("alice".to_owned());
+        story.story_points = Some(3);
+
+        DBState {
+            last_item_id: 3,
+            revision: 0,
+            epics: HashMap::from([(1, epic)]),
+            stories: HashMap::from([(2, story), (3, Story::new("other story".to_owned(), "".to_owned()))]),
+            links: vec![],
+            sprints: HashMap::new()
+        }
+    }
+
+    fn to_string(write: impl FnOnce(&mut Vec<u8>) -> Result<()>) -> String {
+        let mut output = vec![];
+        write(&mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn write_epic_list_should_output_stable_json_schema() {
+        let db_state = sample_state();
+        let output = to_string(|writer| write_epic_list(&epic_views(&db_state), OutputFormat::Json, writer));
+
+        let expected = json!([{
+            "id": 1,
+            "name": "epic",
+            "description": "epic description",
+            "status": "Open",
+            "story_ids": [2, 3],
+            "story_count": 2
+        }]);
+
+        assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), expected);
+    }
+
+    #[test]
+    fn write_story_should_output_stable_json_schema() {
+        let db_state = sample_state();
+        let views = story_views(&db_state, 1).unwrap();
+        let output = to_string(|writer| write_story(&views[0], OutputFormat::Json, writer));
+
+        let expected = json!({
+            "id": 2,
+            "epic_id": 1,
+            "name": "story",
+            "description": "story description",
+            "status": "InProgress",
+            "assignee": "alice",
+            "priority": "Medium",
+            "story_points": 3,
+            "due_date": null
+        });
+
+        assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), expected);
+    }
+
+    #[test]
+    fn write_story_list_should_output_table() {
+        let db_state = sample_state();
+        let output = to_string(|writer| write_story_list(&story_views(&db_state, 1).unwrap(), OutputFormat::Table, writer));
+
+        let lines: Vec<&str> = output.lines().collect();
+
+        let widths = fit_columns(&STORY_LIST_COLUMNS);
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], get_header_string(&["id", "name", "status"], &widths));
+        assert_eq!(lines[1], get_row_string(&["2", "story", "IN PROGRESS"], &widths));
+        assert_eq!(lines[2], get_row_string(&["3", "other story", "OPEN"], &widths));
+    }
+
+    #[test]
+    fn write_story_should_output_planning_in_table() {
+        let db_state = sample_state();
+        let views = story_views(&db_state, 1).unwrap();
+        let output = to_string(|writer| write_story(&views[0], OutputFormat::Table, writer));
+
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], get_row_string(&["2", "1", "story", "story description", "IN PROGRESS"], &fit_columns(&STORY_COLUMNS)));
+        assert_eq!(lines[3], get_header_string(&["assignee", "priority", "points", "due date"], &fit_columns(&PLANNING_COLUMNS)));
+        assert_eq!(lines[4], get_row_string(&["alice", "MEDIUM", "3", "-"], &fit_columns(&PLANNING_COLUMNS)));
+    }
+
+    #[test]
+    fn story_views_should_fail_for_missing_epic() {
+        assert!(story_views(&sample_state(), 999).is_err());
+    }
+
+    #[test]
+    fn output_format_should_parse() {
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("table".parse::<OutputFormat>().unwrap(), OutputFormat::Table);
+        assert_eq!("plain".parse::<OutputFormat>().unwrap(), OutputFormat::Plain);
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::models::{DBState, Status};
+
+/// How many stories of an epic are in each status.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EpicProgress {
+    // indexed like Status::ALL
+    counts: [usize; 4],
+}
+
+impl EpicProgress {
+    pub fn new<'a>(statuses: impl IntoIterator<Item = &'a Status>) -> Self {
+        let mut counts = [0; 4];
+
+        for status in statuses {
+            counts[status_index(status)] += 1;
+        }
+
+        Self { counts }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn count(&self, status: &Status) -> usize {
+        self.counts[status_index(status)]
+    }
+
+    /// Share of the stories in `status`, rounded to the nearest whole percent. 0 without stories.
+    pub fn percent(&self, status: &Status) -> usize {
+        percent(self.count(status), self.total())
+    }
+
+    /// Share of the stories that are resolved or closed.
+    pub fn finished_percent(&self) -> usize {
+        percent(self.count(&Status::Resolved) + self.count(&Status::Closed), self.total())
+    }
+
+    /// A bar such as "#####----- 50%" showing the finished share, `width` columns wide.
+    /// Epics without stories show "-".
+    pub fn bar(&self, width: usize) -> String {
+        if self.total() == 0 {
+            return "-".to_owned();
+        }
+
+        let label = format!(" {:>3}%", self.finished_percent());
+        let bar_width = width.saturating_sub(label.len());
+        let filled = bar_width * self.finished_percent() / 100;
+
+        format!("{}{}{}", "#".repeat(filled), "-".repeat(bar_width - filled), label)
+    }
+
+    /// The status an epic gets from its stories:
+    /// - no stories: `None`, the epic keeps the status it has
+    /// - every story closed: CLOSED
+    /// - every story resolved or closed: RESOLVED
+    /// - every story open: OPEN
+    /// - anything else, i.e. some work started or done: IN PROGRESS
+    pub fn derived_status(&self) -> Option<Status> {
+        let total = self.total();
+
+        if total == 0 {
+            None
+        } else if self.count(&Status::Closed) == total {
+            Some(Status::Closed)
+        } else if self.count(&Status::Resolved) + self.count(&Status::Closed) == total {
+            Some(Status::Resolved)
+        } else if self.count(&Status::Open) == total {
+            Some(Status::Open)
+        } else {
+            Some(Status::InProgress)
+        }
+    }
+}
+
+/// The progress of the epic `epic_id`, `None` if there is no such epic.
+pub fn epic_progress(db_state: &DBState, epic_id: u32) -> Option<EpicProgress> {
+    let epic = db_state.epics.get(&epic_id)?;
+
+    Some(EpicProgress::new(epic.stories.iter().filter_map(|story_id| db_state.stories.get(story_id)).map(|story| &story.status)))
+}
+
+fn status_index(status: &Status) -> usize {
+    Status::ALL.iter().position(|s| s == status).unwrap()
+}
+
+fn percent(count: usize, total: usize) -> usize {
+    if total == 0 {
+        return 0;
+    }
+
+    (count * 100 + total / 2) / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(statuses: &[Status]) -> EpicProgress {
+        EpicProgress::new(statuses)
+    }
+
+    #[test]
+    fn percent_should_round_to_whole_numbers() {
+        let progress = progress(&[Status::Open, Status::InProgress, Status::Closed]);
+
+        assert_eq!(progress.total(), 3);
+        assert_eq!(progress.percent(&Status::Open), 33);
+        assert_eq!(progress.percent(&Status::Resolved), 0);
+        assert_eq!(progress.finished_percent(), 33);
+        assert_eq!(EpicProgress::default().finished_percent(), 0);
+    }
+
+    #[test]
+    fn bar_should_fill_finished_share() {
+        let progress = progress(&[Status::Open, Status::Resolved]);
+
+        assert_eq!(progress.bar(15), "#####-----  50%");
+        assert_eq!(progress.bar(3), "  50%");
+        assert_eq!(EpicProgress::default().bar(15), "-");
+    }
+
+    #[test]
+    fn derived_status_should_follow_rules() {
+        assert_eq!(progress(&[]).derived_status(), None);
+        assert_eq!(progress(&[Status::Closed, Status::Closed]).derived_status(), Some(Status::Closed));
+        assert_eq!(progress(&[Status::Resolved, Status::Closed]).derived_status(), Some(Status::Resolved));
+        assert_eq!(progress(&[Status::Open, Status::Open]).derived_status(), Some(Status::Open));
+        assert_eq!(progress(&[Status::Open, Status::Closed]).derived_status(), Some(Status::InProgress));
+        assert_eq!(progress(&[Status::InProgress]).derived_status(), Some(Status::InProgress));
+    }
+}
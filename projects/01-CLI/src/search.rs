@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::models::DBState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitTarget {
+    Epic { epic_id: u32 },
+    Story { epic_id: u32, story_id: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    pub target: HitTarget,
+    pub score: u32,
+}
+
+impl SearchHit {
+    pub fn id(&self) -> u32 {
+        match self.target {
+            HitTarget::Epic { epic_id } => epic_id,
+            HitTarget::Story { story_id, .. } => story_id,
+        }
+    }
+}
+
+// relevance weights: names count more than descriptions, whole words more than parts of words
+const NAME_WORD_SCORE: u32 = 6;
+const NAME_SUBSTRING_SCORE: u32 = 4;
+const DESCRIPTION_WORD_SCORE: u32 = 3;
+const DESCRIPTION_SUBSTRING_SCORE: u32 = 1;
+// bonus when the whole query appears as typed, e.g. "login page" in "fix the login page"
+const PHRASE_SCORE: u32 = 5;
+
+/// Lowercases `text` and strips accents, so "Café" and "cafe" compare equal.
+pub fn normalize(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect::<String>().to_lowercase()
+}
+
+/// Finds every epic and story whose name or description contains all words of `query`,
+/// ignoring case and accents. Hits are sorted by relevance, then by id.
+pub fn search(db_state: &DBState, query: &str) -> Vec<SearchHit> {
+    let query = normalize(query);
+    let terms: Vec<&str> = query.split_whitespace().collect();
+
+    if terms.is_empty() {
+        return vec![];
+    }
+
+    let mut epic_of_story = HashMap::new();
+
+    for (epic_id, epic) in &db_state.epics {
+        for story_id in &epic.stories {
+            epic_of_story.insert(*story_id, *epic_id);
+        }
+    }
+
+    let epics = db_state.epics.iter()
+        .map(|(epic_id, epic)| (HitTarget::Epic { epic_id: *epic_id }, &epic.name, &epic.description));
+    let stories = db_state.stories.iter()
+        .filter_map(|(story_id, story)| epic_of_story.get(story_id)
+            .map(|epic_id| (HitTarget::Story { epic_id: *epic_id, story_id: *story_id }, &story.name, &story.description)));
+
+    let mut hits: Vec<SearchHit> = epics.chain(stories)
+        .filter_map(|(target, name, description)| score(&terms, &normalize(name), &normalize(description)).map(|score| SearchHit { target, score }))
+        .collect();
+
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.id().cmp(&b.id())));
+
+    hits
+}
+
+// None unless every term matches the name or the description
+fn score(terms: &[&str], name: &str, description: &str) -> Option<u32> {
+    let mut total = 0;
+
+    for term in terms {
+        let term_score = term_score(term, name, NAME_WORD_SCORE, NAME_SUBSTRING_SCORE)
+            + term_score(term, description, DESCRIPTION_WORD_SCORE, DESCRIPTION_SUBSTRING_SCORE);
+
+        if term_score == 0 {
+            return None;
+        }
+
+        total += term_score;
+    }
+
+    let phrase = terms.join(" ");
+
+    if terms.len() > 1 && (name.contains(&phrase) || description.contains(&phrase)) {
+        total += PHRASE_SCORE;
+    }
+
+    Some(total)
+}
+
+fn term_score(term: &str, text: &str, word_score: u32, substring_score: u32) -> u32 {
+    if text.split(|c: char| !c.is_alphanumeric()).any(|word| word == term) {
+        word_score
+    } else if text.contains(term) {
+        substring_score
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Epic, Story};
+
+    use super::*;
+
+    fn sample_state() -> DBState {
+        let mut first_epic = Epic::new("Café redesign".to_owned(), "New menu for the café".to_owned());
+        first_epic.stories = vec![3, 4];
+
+        let second_epic = Epic::new("Billing".to_owned(), "Invoices and payments for the CAFE".to_owned());
+
+        DBState {
+            last_item_id: 4,
+            revision: 0,
+            epics: HashMap::from([(1, first_epic), (2, second_epic)]),
+            stories: HashMap::from([
+                (3, Story::new("Print menus".to_owned(), "Cafeteria style".to_owned())),
+                (4, Story::new("Login page".to_owned(), "Fix the login page layout".to_owned())),
+            ]),
+            links: vec![],
+            sprints: HashMap::new()
+        }
+    }
+
+    #[test]
+    fn normalize_should_strip_case_and_accents() {
+        assert_eq!(normalize("Café CRÈME Ñandú"), "cafe creme nandu");
+    }
+
+    #[test]
+    fn search_should_ignore_case_and_accents() {
+        let hits = search(&sample_state(), "CAFÉ");
+        let ids: Vec<u32> = hits.iter().map(SearchHit::id).collect();
+
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn search_should_rank_names_above_descriptions() {
+        let hits = search(&sample_state(), "cafe");
+
+        // whole word in the name, then whole word in the description, then part of a word
+        assert_eq!(hits[0].target, HitTarget::Epic { epic_id: 1 });
+        assert_eq!(hits[1].target, HitTarget::Epic { epic_id: 2 });
+        assert_eq!(hits[2].target, HitTarget::Story { epic_id: 1, story_id: 3 });
+        assert!(hits[0].score > hits[1].score && hits[1].score > hits[2].score);
+    }
+
+    #[test]
+    fn search_should_require_every_term() {
+        let hits = search(&sample_state(), "login layout");
+
+        assert_eq!(hits, vec![SearchHit { target: HitTarget::Story { epic_id: 1, story_id: 4 }, score: 6 + 3 + 3 }]);
+        assert!(search(&sample_state(), "login invoices").is_empty());
+    }
+
+    #[test]
+    fn search_should_reward_phrase_matches() {
+        let hits = search(&sample_state(), "login page");
+
+        assert_eq!(hits[0].score, 6 + 3 + 6 + 3 + PHRASE_SCORE);
+    }
+
+    #[test]
+    fn search_should_return_nothing_for_blank_query() {
+        assert!(search(&sample_state(), "   ").is_empty());
+    }
+}
@@ -1,3 +1,5 @@
 mod pages;
+mod prompts;
 
-pub use pages::*;
\ No newline at end of file
+pub use pages::*;
+pub use prompts::*;
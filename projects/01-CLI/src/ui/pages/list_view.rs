@@ -0,0 +1,235 @@
+use std::fmt::Display;
+
+use crate::models::{Epic, Status, Story};
+
+pub const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// What the epic and story lists can be filtered and sorted by.
+pub trait ListItem {
+    fn name(&self) -> &str;
+    fn status(&self) -> &Status;
+}
+
+impl ListItem for Epic {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &Status {
+        &self.status
+    }
+}
+
+impl ListItem for Story {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &Status {
+        &self.status
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Name,
+    Status,
+}
+
+impl SortKey {
+    fn next(self) -> Self {
+        match self {
+            Self::Id => Self::Name,
+            Self::Name => Self::Status,
+            Self::Status => Self::Id,
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Id => write!(f, "id"),
+            Self::Name => write!(f, "name"),
+            Self::Status => write!(f, "status"),
+        }
+    }
+}
+
+/// The filter, sort order and current page of a list page. Pages keep one
+/// per list and change it from `handle_input`, so it lives in a `RefCell`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListView {
+    pub status_filter: Option<Status>,
+    pub sort_key: SortKey,
+    // zero based, clamped to the last page when the list shrinks
+    pub page: usize,
+    pub page_size: usize,
+}
+
+impl ListView {
+    pub fn new(page_size: usize) -> Self {
+        Self { status_filter: None, sort_key: SortKey::default(), page: 0, page_size: page_size.max(1) }
+    }
+
+    /// Handles the keys shared by every list page. Returns false for any other input.
+    pub fn handle_input<T: ListItem>(&mut self, input: &str, items: &[(u32, &T)]) -> bool {
+        match input {
+            "f" => {
+                // cycles through no filter and every status
+                let position = self.status_filter.as_ref().and_then(|status| Status::ALL.iter().position(|s| s == status));
+
+                self.status_filter = match position {
+                    None => Some(Status::ALL[0].clone()),
+                    Some(position) => Status::ALL.get(position + 1).cloned()
+                };
+                self.page = 0;
+            }
+            "s" => {
+                self.sort_key = self.sort_key.next();
+                self.page = 0;
+            }
+            "n" => {
+                let count = self.filter(items).len();
+                self.page = (self.current_page(count) + 1).min(self.page_count(count) - 1);
+            }
+            "b" => self.page = self.current_page(self.filter(items).len()).saturating_sub(1),
+            _ => return false
+        }
+
+        true
+    }
+
+    /// Filters and sorts `items` and returns the ones on the current page.
+    pub fn apply<'a, T: ListItem>(&self, items: &[(u32, &'a T)]) -> Vec<(u32, &'a T)> {
+        let mut items = self.filter(items);
+
+        match self.sort_key {
+            SortKey::Id => items.sort_by_key(|(id, _)| *id),
+            SortKey::Name => items.sort_by_key(|(id, item)| (item.name().to_lowercase(), *id)),
+            SortKey::Status => items.sort_by_key(|(id, item)| (Status::ALL.iter().position(|status| status == item.status()), *id)),
+        }
+
+        let page = self.current_page(items.len());
+
+        items.into_iter().skip(page * self.page_size).take(self.page_size).collect()
+    }
+
+    /// The header line describing the active filter, sort order and page.
+    pub fn describe<T: ListItem>(&self, items: &[(u32, &T)]) -> String {
+        let count = self.filter(items).len();
+        let filter = self.status_filter.as_ref().map_or("all".to_owned(), |status| status.to_string());
+
+        format!("filter: {} | sort: {} | page {}/{} | {} items", filter, self.sort_key, self.current_page(count) + 1, self.page_count(count), count)
+    }
+
+    fn filter<'a, T: ListItem>(&self, items: &[(u32, &'a T)]) -> Vec<(u32, &'a T)> {
+        items.iter().filter(|(_, item)| self.status_filter.as_ref().is_none_or(|status| item.status() == status)).copied().collect()
+    }
+
+    fn page_count(&self, count: usize) -> usize {
+        count.div_ceil(self.page_size).max(1)
+    }
+
+    fn current_page(&self, count: usize) -> usize {
+        self.page.min(self.page_count(count) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn story(name: &str, status: Status) -> Story {
+        Story { status, ..Story::new(name.to_owned(), "".to_owned()) }
+    }
+
+    fn ids(items: &[(u32, &Story)]) -> Vec<u32> {
+        items.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn apply_should_sort_by_each_key() {
+        let (a, b, c) = (story("banana", Status::Closed), story("Apple", Status::Open), story("cherry", Status::InProgress));
+        let items = [(3, &a), (1, &b), (2, &c)];
+
+        let mut list = ListView::new(10);
+        assert_eq!(ids(&list.apply(&items)), vec![1, 2, 3]);
+
+        assert!(list.handle_input("s", &items));
+        assert_eq!(ids(&list.apply(&items)), vec![1, 3, 2]);
+
+        assert!(list.handle_input("s", &items));
+        assert_eq!(ids(&list.apply(&items)), vec![1, 2, 3]);
+        assert_eq!(list.sort_key, SortKey::Status);
+
+        assert!(list.handle_input("s", &items));
+        assert_eq!(list.sort_key, SortKey::Id);
+    }
+
+    #[test]
+    fn handle_input_should_cycle_status_filter() {
+        let (a, b) = (story("a", Status::Open), story("b", Status::Closed));
+        let items = [(1, &a), (2, &b)];
+
+        let mut list = ListView::new(10);
+
+        list.handle_input("f", &items);
+        assert_eq!(list.status_filter, Some(Status::Open));
+        assert_eq!(ids(&list.apply(&items)), vec![1]);
+
+        list.handle_input("f", &items);
+        list.handle_input("f", &items);
+        list.handle_input("f", &items);
+        assert_eq!(list.status_filter, Some(Status::Closed));
+        assert_eq!(ids(&list.apply(&items)), vec![2]);
+
+        list.handle_input("f", &items);
+        assert_eq!(list.status_filter, None);
+        assert_eq!(ids(&list.apply(&items)), vec![1, 2]);
+    }
+
+    #[test]
+    fn handle_input_should_page_within_bounds() {
+        let stories: Vec<Story> = (0..5).map(|index| story(&index.to_string(), Status::Open)).collect();
+        let items: Vec<(u32, &Story)> = stories.iter().enumerate().map(|(index, story)| (index as u32 + 1, story)).collect();
+
+        let mut list = ListView::new(2);
+        assert_eq!(ids(&list.apply(&items)), vec![1, 2]);
+
+        list.handle_input("n", &items);
+        list.handle_input("n", &items);
+        list.handle_input("n", &items);
+        assert_eq!(list.page, 2);
+        assert_eq!(ids(&list.apply(&items)), vec![5]);
+        assert_eq!(list.describe(&items), "filter: all | sort: id | page 3/3 | 5 items");
+
+        list.handle_input("b", &items);
+        assert_eq!(ids(&list.apply(&items)), vec![3, 4]);
+
+        list.handle_input("b", &items);
+        list.handle_input("b", &items);
+        assert_eq!(list.page, 0);
+    }
+
+    #[test]
+    fn apply_should_clamp_page_when_list_shrinks() {
+        let (a, b, c) = (story("a", Status::Open), story("b", Status::Open), story("c", Status::Open));
+        let mut list = ListView::new(1);
+        list.page = 2;
+
+        assert_eq!(ids(&list.apply(&[(1, &a), (2, &b), (3, &c)])), vec![3]);
+        assert_eq!(ids(&list.apply(&[(1, &a)])), vec![1]);
+        assert_eq!(list.describe::<Story>(&[]), "filter: all | sort: id | page 1/1 | 0 items");
+    }
+
+    #[test]
+    fn handle_input_should_ignore_other_keys() {
+        let mut list = ListView::new(0);
+
+        assert!(!list.handle_input::<Story>("p", &[]));
+        assert_eq!(list, ListView::new(1));
+    }
+}
@@ -1,43 +1,74 @@
+use std::any::Any;
+use std::cell::RefCell;
 use std::rc::Rc;
 
-use itertools::Itertools;
 use anyhow::Result;
 use anyhow::anyhow;
+use itertools::Itertools;
 
 use crate::db::JiraDatabase;
-use crate::models::Action;
+use crate::progress::{epic_progress, EpicProgress};
+use crate::search::{search, HitTarget};
+use crate::models::{Action, CommentTarget, DBState, Epic, SprintState, Status, Story, StoryPlanning};
 
 mod page_helpers;
-use page_helpers::*;
-
-pub trait Page {
+pub use page_helpers::*;
+
+mod list_view;
+pub use list_view::*;
+
+// fixed column widths, scaled to the terminal width when drawn
+const LIST_COLUMNS: [usize; 4] = [11, 32, 17, 16];
+const STORY_LIST_COLUMNS: [usize; 4] = [11, 32, 17, 10];
+const PLANNED_STORY_LIST_COLUMNS: [usize; 7] = [11, 32, 17, 10, 8, 13, 10];
+const DETAIL_COLUMNS: [usize; 4] = [5, 12, 27, 13];
+const PLANNING_COLUMNS: [usize; 4] = [13, 10, 8, 12];
+const SEARCH_COLUMNS: [usize; 4] = [11, 5, 32, 17];
+const LINK_COLUMNS: [usize; 4] = [12, 11, 32, 17];
+const SPRINT_COLUMNS: [usize; 5] = [11, 26, 10, 12, 12];
+const SPRINT_STORY_COLUMNS: [usize; 4] = [11, 20, 32, 17];
+// one column per status, in Status::ALL order
+const BOARD_COLUMNS: [usize; 4] = [20, 20, 20, 20];
+
+pub trait Page: Any {
     fn draw_page(&self) -> Result<()>;
     fn handle_input(&self, input: &str) -> Result<Option<Action>>;
 }
 
 pub struct HomePage {
-    pub db: Rc<JiraDatabase>
+    pub db: Rc<JiraDatabase>,
+    pub list: RefCell<ListView>
+}
+
+impl HomePage {
+    pub fn new(db: Rc<JiraDatabase>, page_size: usize) -> Self {
+        Self { db, list: RefCell::new(ListView::new(page_size)) }
+    }
 }
+
 impl Page for HomePage {
     fn draw_page(&self) -> Result<()> {
-        println!("----------------------------- EPICS -----------------------------");
-        println!("     id     |               name               |      status      ");
+        let db_state = self.db.read_db()?;
+        let items: Vec<(u32, &Epic)> = db_state.epics.iter().map(|(id, epic)| (*id, epic)).collect();
+        let list = self.list.borrow();
+
+        let widths = fit_columns(&LIST_COLUMNS);
+
+        println!("{}", get_title_string("EPICS", &widths));
+        println!("{}", list.describe(&items));
+        println!("{}", get_header_string(&["id", "name", "status", "progress"], &widths));
 
         // TODO: print out epics using get_column_string(). also make sure the epics are sorted by id
-        let epics = self.db.read_db()?.epics;
+        for (id, epic) in list.apply(&items) {
+            let progress = epic_progress(&db_state, id).unwrap_or_default();
 
-        for id in epics.keys().sorted() {
-            let epic = &epics[id];
-            let id_col = get_column_string(&id.to_string(), 11);
-            let name_col = get_column_string(&epic.name, 32);
-            let status_col = get_column_string(&epic.status.to_string(), 17);
-            println!("{} | {} | {}", id_col, name_col, status_col);
+            println!("{}", get_row_string(&[&id.to_string(), &epic.name, &epic.status.to_string(), &progress.bar(widths[3])], &widths));
         }
 
         println!();
         println!();
 
-        println!("[q] quit | [c] create epic | [:id:] navigate to epic");
+        println!("[q] quit | [c] create epic | [/] search | [:id:] navigate to epic | [o] board | [v] sprints | [a] active sprint | [f] filter | [s] sort | [n] next page | [b] previous page | [z] undo | [y] redo");
 
         Ok(())
     }
@@ -45,10 +76,22 @@ impl Page for HomePage {
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         //todo!() // match against the user input and return the corresponding action. If the user input was invalid return None.
         let epics = self.db.read_db()?.epics;
+        let items: Vec<(u32, &Epic)> = epics.iter().map(|(id, epic)| (*id, epic)).collect();
+
+        // filter, sort and paging keys only change how the list is drawn
+        if self.list.borrow_mut().handle_input(input, &items) {
+            return Ok(None);
+        }
 
         match input {
             "q" => Ok(Some(Action::Exit)),
             "c" => Ok(Some(Action::CreateEpic)),
+            "/" => Ok(Some(Action::Search)),
+            "o" => Ok(Some(Action::NavigateToBoard { epic_id: None })),
+            "v" => Ok(Some(Action::NavigateToSprints)),
+            "a" => Ok(Some(Action::NavigateToActiveSprint)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
             input => {
                 if let Ok(id) = input.parse::<u32>() {
                     if epics.contains_key(&id) {
@@ -63,7 +106,14 @@ impl Page for HomePage {
 
 pub struct EpicDetail {
     pub epic_id: u32,
-    pub db: Rc<JiraDatabase>
+    pub db: Rc<JiraDatabase>,
+    pub list: RefCell<ListView>
+}
+
+impl EpicDetail {
+    pub fn new(epic_id: u32, db: Rc<JiraDatabase>, page_size: usize) -> Self {
+        Self { epic_id, db, list: RefCell::new(ListView::new(page_size)) }
+    }
 }
 
 impl Page for EpicDetail {
@@ -71,50 +121,88 @@ impl Page for EpicDetail {
         let db_state = self.db.read_db()?;
         let epic = db_state.epics.get(&self.epic_id).ok_or_else(|| anyhow!("could not find epic!"))?;
 
-        println!("------------------------------ EPIC ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
+        let widths = fit_columns(&DETAIL_COLUMNS);
 
-        // TODO: print out epic details using get_column_string()
+        println!("{}", get_title_string("EPIC", &widths));
+        println!("{}", get_header_string(&["id", "name", "description", "status"], &widths));
 
-        let id_col = get_column_string(&self.epic_id.to_string(), 5);
-        let name_col = get_column_string(&epic.name, 12);
-        let desc_col = get_column_string(&epic.description, 27);
-        let status_col = get_column_string(&epic.status.to_string(), 13);
-        println!("{} | {} | {} | {}", id_col, name_col, desc_col, status_col);
+        // TODO: print out epic details using get_column_string()
+        println!("{}", get_row_string(&[&self.epic_id.to_string(), &epic.name, &epic.description, &epic.status.to_string()], &widths));
   
         println!();
-
-        println!("---------------------------- STORIES ----------------------------");
-        println!("     id     |               name               |      status      ");
+        println!("status: {}", if epic.derive_status { "derived from stories" } else { "set by hand" });
+        println!("progress: {}", describe_progress(&epic_progress(&db_state, self.epic_id).unwrap_or_default()));
+        println!();
+        draw_comments(&epic.comments);
+        println!();
+        draw_history(epic.created_at, epic.updated_at, &epic.history);
+        println!();
 
         let stories = &db_state.stories;
 
+        // the planning columns are only shown once some story in the epic is planned
+        let show_planning = epic.stories.iter().any(|id| stories[id].planning() != StoryPlanning::default());
+
+        let items: Vec<(u32, &Story)> = epic.stories.iter().map(|id| (*id, &stories[id])).collect();
+        let list = self.list.borrow();
+
+        let widths = fit_columns(if show_planning { &PLANNED_STORY_LIST_COLUMNS } else { &STORY_LIST_COLUMNS });
+
+        println!("{}", get_title_string("STORIES", &widths));
+        println!("{}", list.describe(&items));
+
+        if show_planning {
+            println!("{}", get_header_string(&["id", "name", "status", "priority", "points", "assignee", "checklist"], &widths));
+        } else {
+            println!("{}", get_header_string(&["id", "name", "status", "checklist"], &widths));
+        }
+
         // TODO: print out stories using get_column_string(). also make sure the stories are sorted by id
-        for id in epic.stories.iter().sorted() {
-            let story = &stories[id];
-            let id_col = get_column_string(&id.to_string(), 11);
-            let name_col = get_column_string(&story.name, 32);
-            let status_col = get_column_string(&story.status.to_string(), 17);
-            println!("{} | {} | {}", id_col, name_col, status_col);
+        for (id, story) in list.apply(&items) {
+            if show_planning {
+                let points = story.story_points.map_or("-".to_owned(), |points| points.to_string());
+                println!("{}", get_row_string(&[&id.to_string(), &story.name, &story.status.to_string(), &story.priority.to_string(),
+                    &points, story.assignee.as_deref().unwrap_or("-"), &story.checklist_progress()], &widths));
+            } else {
+                println!("{}", get_row_string(&[&id.to_string(), &story.name, &story.status.to_string(), &story.checklist_progress()], &widths));
+            }
         }
 
         println!();
         println!();
 
-        println!("[p] previous | [u] update epic | [d] delete epic | [c] create story | [:id:] navigate to story");
+        println!("[p] previous | [u] update epic | [e] edit epic | [t] toggle derived status | [d] delete epic | [c] create story | [:id:] navigate to story | [o] board | [a] add comment | [r] edit comment | [x] delete comment | [f] filter | [s] sort | [n] next page | [b] previous page | [z] undo | [y] redo");
 
         Ok(())
     }
 
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         //todo!() // match against the user input and return the corresponding action. If the user input was invalid return None.
-        let stories = self.db.read_db()?.stories;
+        let db_state = self.db.read_db()?;
+        let stories = &db_state.stories;
+
+        if let Some(epic) = db_state.epics.get(&self.epic_id) {
+            let items: Vec<(u32, &Story)> = epic.stories.iter().filter_map(|id| stories.get(id).map(|story| (*id, story))).collect();
+
+            // filter, sort and paging keys only change how the list is drawn
+            if self.list.borrow_mut().handle_input(input, &items) {
+                return Ok(None);
+            }
+        }
 
         match input {
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
             "u" => Ok(Some(Action::UpdateEpicStatus { epic_id: self.epic_id })),
+            "e" => Ok(Some(Action::UpdateEpicDetails { epic_id: self.epic_id })),
+            "t" => Ok(Some(Action::ToggleDerivedStatus { epic_id: self.epic_id })),
             "d" => Ok(Some(Action::DeleteEpic { epic_id: self.epic_id })),
             "c" => Ok(Some(Action::CreateStory { epic_id: self.epic_id })),
+            "o" => Ok(Some(Action::NavigateToBoard { epic_id: Some(self.epic_id) })),
+            "a" => Ok(Some(Action::AddComment { target: CommentTarget::Epic { epic_id: self.epic_id } })),
+            "r" => Ok(Some(Action::EditComment { target: CommentTarget::Epic { epic_id: self.epic_id } })),
+            "x" => Ok(Some(Action::DeleteComment { target: CommentTarget::Epic { epic_id: self.epic_id } })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
             input => {
                 if let Ok(id) = input.parse::<u32>() {
                     if stories.contains_key(&id) {
@@ -138,20 +226,59 @@ impl Page for StoryDetail {
         let db_state = self.db.read_db()?;
         let story = db_state.stories.get(&self.story_id).ok_or_else(|| anyhow!("could not find story!"))?;
 
-        println!("------------------------------ STORY ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
+        let widths = fit_columns(&DETAIL_COLUMNS);
+
+        println!("{}", get_title_string("STORY", &widths));
+        println!("{}", get_header_string(&["id", "name", "description", "status"], &widths));
         
         // TODO: print out story details using get_column_string()
-        let id_col = get_column_string(&self.story_id.to_string(), 5);
-        let name_col = get_column_string(&story.name, 12);
-        let desc_col = get_column_string(&story.description, 27);
-        let status_col = get_column_string(&story.status.to_string(), 13);
-        println!("{} | {} | {} | {}", id_col, name_col, desc_col, status_col);
+        println!("{}", get_row_string(&[&self.story_id.to_string(), &story.name, &story.description, &story.status.to_string()], &widths));
+
+        println!();
+        draw_comments(&story.comments);
+
+        let widths = fit_columns(&PLANNING_COLUMNS);
+
+        println!();
+        println!("{}", get_header_string(&["assignee", "priority", "points", "due date"], &widths));
+
+        let points = story.story_points.map_or("-".to_owned(), |points| points.to_string());
+        let due_date = story.due_date.map_or("-".to_owned(), |date| date.to_string());
+        println!("{}", get_row_string(&[story.assignee.as_deref().unwrap_or("-"), &story.priority.to_string(), &points, &due_date], &widths));
+
+        let sprint = story.sprint_id.and_then(|sprint_id| db_state.sprints.get(&sprint_id).map(|sprint| format!("{} ({})", sprint.name, sprint.state)));
+
+        println!();
+        println!("sprint: {}", sprint.as_deref().unwrap_or("backlog"));
+        println!("checklist: {}", story.checklist_progress());
+
+        for (index, item) in story.checklist.iter().enumerate() {
+            println!("{:>3}. [{}] {}", index + 1, if item.done { "x" } else { " " }, item.text);
+        }
+
+        let widths = fit_columns(&LINK_COLUMNS);
+
+        println!();
+        println!("{}", get_title_string("LINKS", &widths));
+        println!("{}", get_header_string(&["link", "id", "name", "status"], &widths));
+
+        // outbound links first, then the ones pointing at this story
+        let links = db_state.links.iter().filter(|link| link.involves(self.story_id)).sorted_by_key(|link| link.from != self.story_id);
+
+        for link in links {
+            let (label, other_id) = link.seen_from(self.story_id);
+            let (name, status) = db_state.stories.get(&other_id).map_or(("-".to_owned(), "-".to_owned()), |other| (other.name.clone(), other.status.to_string()));
+
+            println!("{}", get_row_string(&[label, &other_id.to_string(), &name, &status], &widths));
+        }
+
+        println!();
+        draw_history(story.created_at, story.updated_at, &story.history);
         
         println!();
         println!();
 
-        println!("[p] previous | [u] update story | [d] delete story");
+        println!("[p] previous | [u] update story | [e] edit story | [l] plan story | [m] move story | [s] set sprint | [d] delete story | [a] add comment | [r] edit comment | [x] delete comment | [i] add item | [t] toggle item | [o] reorder item | [k] delete item | [+] link story | [-] remove link | [z] undo | [y] redo");
 
         Ok(())
     }
@@ -161,7 +288,22 @@ impl Page for StoryDetail {
         match input {
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
             "u" => Ok(Some(Action::UpdateStoryStatus { story_id: self.story_id })),
+            "e" => Ok(Some(Action::UpdateStoryDetails { story_id: self.story_id })),
+            "l" => Ok(Some(Action::UpdateStoryPlanning { story_id: self.story_id })),
+            "m" => Ok(Some(Action::MoveStory { epic_id: self.epic_id, story_id: self.story_id })),
+            "s" => Ok(Some(Action::AssignStoryToSprint { story_id: self.story_id })),
             "d" => Ok(Some(Action::DeleteStory { epic_id: self.epic_id, story_id: self.story_id })),
+            "a" => Ok(Some(Action::AddComment { target: CommentTarget::Story { story_id: self.story_id } })),
+            "r" => Ok(Some(Action::EditComment { target: CommentTarget::Story { story_id: self.story_id } })),
+            "x" => Ok(Some(Action::DeleteComment { target: CommentTarget::Story { story_id: self.story_id } })),
+            "i" => Ok(Some(Action::AddChecklistItem { story_id: self.story_id })),
+            "t" => Ok(Some(Action::ToggleChecklistItem { story_id: self.story_id })),
+            "o" => Ok(Some(Action::MoveChecklistItem { story_id: self.story_id })),
+            "k" => Ok(Some(Action::DeleteChecklistItem { story_id: self.story_id })),
+            "+" => Ok(Some(Action::LinkStory { story_id: self.story_id })),
+            "-" => Ok(Some(Action::UnlinkStory { story_id: self.story_id })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
             _ => {
                 Ok(None)
             }
@@ -169,40 +311,334 @@ impl Page for StoryDetail {
     }
 }
 
+pub struct SearchResults {
+    pub query: String,
+    pub db: Rc<JiraDatabase>
+}
+
+impl Page for SearchResults {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let hits = search(&db_state, &self.query);
+
+        let widths = fit_columns(&SEARCH_COLUMNS);
+
+        println!("{}", get_title_string("SEARCH", &widths));
+        println!("results for \"{}\": {}", self.query, hits.len());
+        println!();
+        println!("{}", get_header_string(&["id", "type", "name", "status"], &widths));
+
+        // hits are already sorted by relevance
+        for hit in &hits {
+            let (kind, name, status) = match hit.target {
+                HitTarget::Epic { epic_id } => ("epic", &db_state.epics[&epic_id].name, &db_state.epics[&epic_id].status),
+                HitTarget::Story { story_id, .. } => ("story", &db_state.stories[&story_id].name, &db_state.stories[&story_id].status),
+            };
+
+            println!("{}", get_row_string(&[&hit.id().to_string(), kind, name, &status.to_string()], &widths));
+        }
+
+        println!();
+        println!();
+
+        println!("[p] previous | [:id:] navigate to epic or story | [z] undo | [y] redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
+
+        match input {
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input => {
+                if let Ok(id) = input.parse::<u32>() {
+                    // epics and stories share one id sequence, so an id identifies the hit
+                    if let Some(hit) = search(&db_state, &self.query).into_iter().find(|hit| hit.id() == id) {
+                        return match hit.target {
+                            HitTarget::Epic { epic_id } => Ok(Some(Action::NavigateToEpicDetail { epic_id })),
+                            HitTarget::Story { epic_id, story_id } => Ok(Some(Action::NavigateToStoryDetail { epic_id, story_id })),
+                        };
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+// e.g. "1 OPEN (25%) | 3 CLOSED (75%)", leaving out the statuses no story is in
+fn describe_progress(progress: &EpicProgress) -> String {
+    if progress.total() == 0 {
+        return "no stories".to_owned();
+    }
+
+    Status::ALL.iter()
+        .filter(|status| progress.count(status) > 0)
+        .map(|status| format!("{} {} ({}%)", progress.count(status), status, progress.percent(status)))
+        .join(" | ")
+}
+
+/// Stories as cards in one column per status, for one epic or for all of them.
+/// A card is selected by typing its id and then moved to the neighbouring columns.
+pub struct BoardPage {
+    pub epic_id: Option<u32>,
+    pub db: Rc<JiraDatabase>,
+    pub selected: RefCell<Option<u32>>
+}
+
+impl BoardPage {
+    pub fn new(epic_id: Option<u32>, db: Rc<JiraDatabase>) -> Self {
+        Self { epic_id, db, selected: RefCell::new(None) }
+    }
+
+    /// The cards of every column, sorted by id.
+    fn columns<'a>(&self, db_state: &'a DBState) -> Result<Vec<Vec<(u32, &'a Story)>>> {
+        let story_ids: Vec<u32> = match self.epic_id {
+            Some(epic_id) => db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("could not find epic!"))?.stories.clone(),
+            None => db_state.epics.values().flat_map(|epic| epic.stories.iter().copied()).collect()
+        };
+
+        let cards: Vec<(u32, &Story)> = story_ids.into_iter().filter_map(|id| db_state.stories.get(&id).map(|story| (id, story))).sorted_by_key(|(id, _)| *id).collect();
+
+        Ok(Status::ALL.iter().map(|status| cards.iter().filter(|(_, story)| story.status == *status).copied().collect()).collect())
+    }
+
+    /// The nearest column left or right of `column` the workflow lets a card move to,
+    /// so e.g. a resolved card moves left straight to OPEN when it can't go back to IN PROGRESS.
+    fn next_column(&self, column: usize, left: bool) -> Option<Status> {
+        let targets = self.db.workflow.next_statuses(&Status::ALL[column]).into_iter()
+            .filter_map(|status| Status::ALL.iter().position(|other| *other == status));
+
+        let target = if left { targets.filter(|target| *target < column).max() } else { targets.filter(|target| *target > column).min() };
+
+        target.map(|target| Status::ALL[target].clone())
+    }
+}
+
+impl Page for BoardPage {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let columns = self.columns(&db_state)?;
+        let selected = *self.selected.borrow();
+
+        let title = match self.epic_id {
+            Some(epic_id) => format!("BOARD: {}", db_state.epics[&epic_id].name),
+            None => "BOARD: ALL EPICS".to_owned()
+        };
+
+        let widths = fit_columns(&BOARD_COLUMNS);
+        let labels: Vec<String> = Status::ALL.iter().zip(&columns).map(|(status, cards)| format!("{} ({})", status, cards.len())).collect();
+
+        println!("{}", get_title_string(&title, &widths));
+        println!("{}", get_header_string(&labels.iter().map(String::as_str).collect::<Vec<_>>(), &widths));
+
+        let rows = columns.iter().map(Vec::len).max().unwrap_or_default();
+
+        for row in 0..rows {
+            // long titles are cut by get_row_string, the selected card is marked with ">"
+            let cards: Vec<String> = columns.iter().map(|cards| cards.get(row).map_or("".to_owned(), |(id, story)| {
+                format!("{}{} {}", if selected == Some(*id) { ">" } else { " " }, id, story.name)
+            })).collect();
+
+            println!("{}", get_row_string(&cards.iter().map(String::as_str).collect::<Vec<_>>(), &widths));
+        }
+
+        println!();
+        println!();
+
+        println!("[p] previous | [:id:] select card | [<] move card left | [>] move card right | [v] view story | [z] undo | [y] redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
+        let columns = self.columns(&db_state)?;
+
+        // the column the selected card is in, if it is still on the board
+        let selected = (*self.selected.borrow()).and_then(|story_id| {
+            columns.iter().position(|cards| cards.iter().any(|(id, _)| *id == story_id)).map(|column| (story_id, column))
+        });
+
+        match input {
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "<" => Ok(selected.and_then(|(story_id, column)| self.next_column(column, true).map(|status| Action::MoveCard { story_id, status }))),
+            ">" => Ok(selected.and_then(|(story_id, column)| self.next_column(column, false).map(|status| Action::MoveCard { story_id, status }))),
+            "v" => Ok(selected.and_then(|(story_id, _)| db_state.epic_of_story(story_id).map(|epic_id| Action::NavigateToStoryDetail { epic_id, story_id }))),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input => {
+                if let Ok(id) = input.parse::<u32>() {
+                    if columns.iter().flatten().any(|(story_id, _)| *story_id == id) {
+                        *self.selected.borrow_mut() = Some(id);
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+pub struct SprintsPage {
+    pub db: Rc<JiraDatabase>
+}
+
+impl Page for SprintsPage {
+    fn draw_page(&self) -> Result<()> {
+        let sprints = self.db.read_db()?.sprints;
+
+        let widths = fit_columns(&SPRINT_COLUMNS);
+
+        println!("{}", get_title_string("SPRINTS", &widths));
+        println!("{}", get_header_string(&["id", "name", "state", "start", "end"], &widths));
+
+        for (id, sprint) in sprints.iter().sorted_by_key(|(id, sprint)| (sprint.start_date, **id)) {
+            println!("{}", get_row_string(&[&id.to_string(), &sprint.name, &sprint.state.to_string(), &sprint.start_date.to_string(), &sprint.end_date.to_string()], &widths));
+        }
+
+        println!();
+        println!();
+
+        println!("[p] previous | [c] create sprint | [:id:] navigate to sprint | [z] undo | [y] redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let sprints = self.db.read_db()?.sprints;
+
+        match input {
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "c" => Ok(Some(Action::CreateSprint)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input => {
+                if let Ok(id) = input.parse::<u32>() {
+                    if sprints.contains_key(&id) {
+                        return Ok(Some(Action::NavigateToSprintDetail { sprint_id: id }));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+pub struct SprintDetail {
+    pub sprint_id: u32,
+    pub db: Rc<JiraDatabase>
+}
+
+impl Page for SprintDetail {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let sprint = db_state.sprints.get(&self.sprint_id).ok_or_else(|| anyhow!("could not find sprint!"))?;
+
+        let widths = fit_columns(&SPRINT_COLUMNS);
+
+        println!("{}", get_title_string("SPRINT", &widths));
+        println!("{}", get_header_string(&["id", "name", "state", "start", "end"], &widths));
+        println!("{}", get_row_string(&[&self.sprint_id.to_string(), &sprint.name, &sprint.state.to_string(), &sprint.start_date.to_string(), &sprint.end_date.to_string()], &widths));
+
+        let story_ids = db_state.sprint_stories(self.sprint_id);
+        let finished = story_ids.iter().filter(|id| db_state.stories[id].status.is_finished()).count();
+
+        println!();
+        println!("goal: {}", if sprint.goal.is_empty() { "-" } else { &sprint.goal });
+        println!("progress: {}/{} stories finished", finished, story_ids.len());
+        println!();
+        draw_history(sprint.created_at, sprint.updated_at, &sprint.history);
+        println!();
+
+        let widths = fit_columns(&SPRINT_STORY_COLUMNS);
+
+        println!("{}", get_title_string("STORIES", &widths));
+        println!("{}", get_header_string(&["id", "epic", "name", "status"], &widths));
+
+        // stories come from every epic, so each row names its epic
+        for story_id in story_ids {
+            let story = &db_state.stories[&story_id];
+            let epic = db_state.epic_of_story(story_id).map_or("-".to_owned(), |epic_id| db_state.epics[&epic_id].name.clone());
+
+            println!("{}", get_row_string(&[&story_id.to_string(), &epic, &story.name, &story.status.to_string()], &widths));
+        }
+
+        println!();
+        println!();
+
+        match sprint.state {
+            SprintState::Planned => println!("[p] previous | [t] start sprint | [:id:] navigate to story | [z] undo | [y] redo"),
+            SprintState::Active => println!("[p] previous | [x] close sprint | [:id:] navigate to story | [z] undo | [y] redo"),
+            SprintState::Closed => println!("[p] previous | [:id:] navigate to story | [z] undo | [y] redo"),
+        }
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let db_state = self.db.read_db()?;
+        let state = db_state.sprints.get(&self.sprint_id).map(|sprint| sprint.state.clone());
+
+        match input {
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "t" if state == Some(SprintState::Planned) => Ok(Some(Action::StartSprint { sprint_id: self.sprint_id })),
+            "x" if state == Some(SprintState::Active) => Ok(Some(Action::CloseSprint { sprint_id: self.sprint_id })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input => {
+                if let Ok(id) = input.parse::<u32>() {
+                    if db_state.stories.get(&id).is_some_and(|story| story.sprint_id == Some(self.sprint_id)) {
+                        if let Some(epic_id) = db_state.epic_of_story(id) {
+                            return Ok(Some(Action::NavigateToStoryDetail { epic_id, story_id: id }));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
+// several tests compare booleans with assert_eq!
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
     use crate::{db::test_utils::MockDB};
-    use crate::models::{Epic, Story};
+    use crate::models::{Date, LinkType, Sprint, Status, StoryLink};
+    use crate::workflow::Workflow;
 
     mod home_page {
         use super::*;
 
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = HomePage { db };
+            let page = HomePage::new(db, DEFAULT_PAGE_SIZE);
             assert_eq!(page.draw_page().is_ok(), true);
         }
         
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = HomePage { db };
+            let page = HomePage::new(db, DEFAULT_PAGE_SIZE);
             assert_eq!(page.handle_input("").is_ok(), true);
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic = Epic::new("".to_owned(), "".to_owned());
 
             let epic_id = db.create_epic(epic).unwrap();
 
-            let page = HomePage { db };
+            let page = HomePage::new(db, DEFAULT_PAGE_SIZE);
 
             let q = "q";
             let c = "c";
@@ -214,12 +650,33 @@ mod tests {
 
             assert_eq!(page.handle_input(q).unwrap(), Some(Action::Exit));
             assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateEpic));
+            assert_eq!(page.handle_input("/").unwrap(), Some(Action::Search));
+            assert_eq!(page.handle_input("o").unwrap(), Some(Action::NavigateToBoard { epic_id: None }));
+            assert_eq!(page.handle_input("v").unwrap(), Some(Action::NavigateToSprints));
+            assert_eq!(page.handle_input("a").unwrap(), Some(Action::NavigateToActiveSprint));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(page.handle_input(&valid_epic_id).unwrap(), Some(Action::NavigateToEpicDetail { epic_id: 1 }));
             assert_eq!(page.handle_input(invalid_epic_id).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
             assert_eq!(page.handle_input(junk_input_with_valid_prefix).unwrap(), None);
             assert_eq!(page.handle_input(input_with_trailing_white_spaces).unwrap(), None);
         }
+
+        #[test]
+        fn handle_input_should_update_list_view() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let page = HomePage::new(db, DEFAULT_PAGE_SIZE);
+
+            assert_eq!(page.handle_input("f").unwrap(), None);
+            assert_eq!(page.handle_input("s").unwrap(), None);
+            assert_eq!(page.handle_input("n").unwrap(), None);
+            assert_eq!(page.handle_input("b").unwrap(), None);
+            assert_eq!(page.list.borrow().status_filter, Some(Status::Open));
+            assert_eq!(page.list.borrow().sort_key, SortKey::Name);
+            assert!(page.draw_page().is_ok());
+        }
     }
 
     mod epic_detail_page {
@@ -227,38 +684,49 @@ mod tests {
 
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
-            let page = EpicDetail { epic_id, db };
+            let page = EpicDetail::new(epic_id, db, DEFAULT_PAGE_SIZE);
             assert_eq!(page.draw_page().is_ok(), true);
         }
 
+        #[test]
+        fn draw_page_should_not_throw_error_with_planned_stories() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            db.update_story_planning(story_id, StoryPlanning { story_points: Some(3), ..StoryPlanning::default() }).unwrap();
+
+            let page = EpicDetail::new(epic_id, db, DEFAULT_PAGE_SIZE);
+            assert!(page.draw_page().is_ok());
+        }
+
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
 
-            let page = EpicDetail { epic_id, db };
+            let page = EpicDetail::new(epic_id, db, DEFAULT_PAGE_SIZE);
             assert_eq!(page.handle_input("").is_ok(), true);
         }
 
         #[test]
         fn draw_page_should_throw_error_for_invalid_epic_id() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
-            let page = EpicDetail { epic_id: 999, db };
+            let page = EpicDetail::new(999, db, DEFAULT_PAGE_SIZE);
             assert_eq!(page.draw_page().is_err(), true);
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
 
-            let page = EpicDetail { epic_id, db };
+            let page = EpicDetail::new(epic_id, db, DEFAULT_PAGE_SIZE);
 
             let p = "p";
             let u = "u";
@@ -271,8 +739,15 @@ mod tests {
 
             assert_eq!(page.handle_input(p).unwrap(), Some(Action::NavigateToPreviousPage));
             assert_eq!(page.handle_input(u).unwrap(), Some(Action::UpdateEpicStatus { epic_id: 1 }));
+            assert_eq!(page.handle_input("e").unwrap(), Some(Action::UpdateEpicDetails { epic_id: 1 }));
+            assert_eq!(page.handle_input("t").unwrap(), Some(Action::ToggleDerivedStatus { epic_id: 1 }));
             assert_eq!(page.handle_input(d).unwrap(), Some(Action::DeleteEpic { epic_id: 1 }));
             assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateStory { epic_id: 1 }));
+            assert_eq!(page.handle_input("a").unwrap(), Some(Action::AddComment { target: CommentTarget::Epic { epic_id: 1 } }));
+            assert_eq!(page.handle_input("r").unwrap(), Some(Action::EditComment { target: CommentTarget::Epic { epic_id: 1 } }));
+            assert_eq!(page.handle_input("x").unwrap(), Some(Action::DeleteComment { target: CommentTarget::Epic { epic_id: 1 } }));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), Some(Action::NavigateToStoryDetail { epic_id: 1, story_id: 2 }));
             assert_eq!(page.handle_input(invalid_story_id).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
@@ -286,7 +761,7 @@ mod tests {
 
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
@@ -297,7 +772,7 @@ mod tests {
 
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
@@ -306,9 +781,46 @@ mod tests {
             assert_eq!(page.handle_input("").is_ok(), true);
         }
 
+        #[test]
+        fn draw_page_should_not_throw_error_with_history() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            db.update_story_status(story_id, Status::InProgress).unwrap();
+
+            let page = StoryDetail { epic_id, story_id, db };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn draw_page_should_not_throw_error_with_comments_checklist_and_links() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            for index in 0..(COMMENTS_LENGTH + 2) {
+                db.add_comment(CommentTarget::Story { story_id }, "ana".to_owned(), format!("comment {}", index)).unwrap();
+            }
+
+            db.update_comment(CommentTarget::Story { story_id }, 7, "edited".to_owned()).unwrap();
+            db.add_checklist_item(story_id, "write".to_owned()).unwrap();
+            db.toggle_checklist_item(story_id, 0).unwrap();
+
+            let other_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            db.link_stories(StoryLink { from: other_story_id, to: story_id, link_type: LinkType::Blocks }).unwrap();
+
+            let sprint_id = db.create_sprint(Sprint::new("sprint".to_owned(), "".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap())).unwrap();
+            db.assign_story_to_sprint(story_id, Some(sprint_id)).unwrap();
+
+            let page = StoryDetail { epic_id, story_id, db };
+            assert!(page.draw_page().is_ok());
+        }
+
         #[test]
         fn draw_page_should_throw_error_for_invalid_story_id() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let _ = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
@@ -319,7 +831,7 @@ mod tests {
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase { database: Box::new(MockDB::new()) });
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
 
             let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
             let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
@@ -336,11 +848,208 @@ mod tests {
 
             assert_eq!(page.handle_input(p).unwrap(), Some(Action::NavigateToPreviousPage));
             assert_eq!(page.handle_input(u).unwrap(), Some(Action::UpdateStoryStatus { story_id }));
+            assert_eq!(page.handle_input("e").unwrap(), Some(Action::UpdateStoryDetails { story_id }));
+            assert_eq!(page.handle_input("l").unwrap(), Some(Action::UpdateStoryPlanning { story_id }));
+            assert_eq!(page.handle_input("m").unwrap(), Some(Action::MoveStory { epic_id, story_id }));
+            assert_eq!(page.handle_input("s").unwrap(), Some(Action::AssignStoryToSprint { story_id }));
             assert_eq!(page.handle_input(d).unwrap(), Some(Action::DeleteStory { epic_id, story_id }));
+            assert_eq!(page.handle_input("a").unwrap(), Some(Action::AddComment { target: CommentTarget::Story { story_id } }));
+            assert_eq!(page.handle_input("r").unwrap(), Some(Action::EditComment { target: CommentTarget::Story { story_id } }));
+            assert_eq!(page.handle_input("x").unwrap(), Some(Action::DeleteComment { target: CommentTarget::Story { story_id } }));
+            assert_eq!(page.handle_input("i").unwrap(), Some(Action::AddChecklistItem { story_id }));
+            assert_eq!(page.handle_input("t").unwrap(), Some(Action::ToggleChecklistItem { story_id }));
+            assert_eq!(page.handle_input("o").unwrap(), Some(Action::MoveChecklistItem { story_id }));
+            assert_eq!(page.handle_input("k").unwrap(), Some(Action::DeleteChecklistItem { story_id }));
+            assert_eq!(page.handle_input("+").unwrap(), Some(Action::LinkStory { story_id }));
+            assert_eq!(page.handle_input("-").unwrap(), Some(Action::UnlinkStory { story_id }));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(page.handle_input(some_number).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
             assert_eq!(page.handle_input(junk_input_with_valid_prefix).unwrap(), None);
             assert_eq!(page.handle_input(input_with_trailing_white_spaces).unwrap(), None);
         } 
     }
-}
\ No newline at end of file
+
+    mod search_results_page {
+        use super::*;
+
+        #[test]
+        fn draw_page_should_not_throw_error() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("login".to_owned(), "".to_owned())).unwrap();
+            db.create_story(Story::new("login page".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let page = SearchResults { query: "login".to_owned(), db };
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn handle_input_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("Login".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("Lógin page".to_owned(), "".to_owned()), epic_id).unwrap();
+            let other_story_id = db.create_story(Story::new("Billing".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let page = SearchResults { query: "login".to_owned(), db };
+
+            assert_eq!(page.handle_input("p").unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input(&epic_id.to_string()).unwrap(), Some(Action::NavigateToEpicDetail { epic_id }));
+            assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), Some(Action::NavigateToStoryDetail { epic_id, story_id }));
+            assert_eq!(page.handle_input(&other_story_id.to_string()).unwrap(), None);
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("j983f2j").unwrap(), None);
+        }
+    }
+
+    mod sprint_pages {
+        use super::*;
+
+        fn sprint(name: &str) -> Sprint {
+            Sprint::new(name.to_owned(), "".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap())
+        }
+
+        #[test]
+        fn sprints_page_should_return_the_correct_actions() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let sprint_id = db.create_sprint(sprint("sprint")).unwrap();
+
+            let page = SprintsPage { db };
+
+            assert!(page.draw_page().is_ok());
+            assert_eq!(page.handle_input("p").unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input("c").unwrap(), Some(Action::CreateSprint));
+            assert_eq!(page.handle_input(&sprint_id.to_string()).unwrap(), Some(Action::NavigateToSprintDetail { sprint_id }));
+            assert_eq!(page.handle_input("999").unwrap(), None);
+        }
+
+        #[test]
+        fn sprint_detail_should_list_stories_across_epics() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let first_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let second_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let first_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), first_epic_id).unwrap();
+            let second_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), second_epic_id).unwrap();
+            let unplanned_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), second_epic_id).unwrap();
+            let sprint_id = db.create_sprint(sprint("sprint")).unwrap();
+
+            db.assign_story_to_sprint(first_story_id, Some(sprint_id)).unwrap();
+            db.assign_story_to_sprint(second_story_id, Some(sprint_id)).unwrap();
+
+            let page = SprintDetail { sprint_id, db: Rc::clone(&db) };
+
+            assert!(page.draw_page().is_ok());
+            assert_eq!(page.handle_input(&first_story_id.to_string()).unwrap(), Some(Action::NavigateToStoryDetail { epic_id: first_epic_id, story_id: first_story_id }));
+            assert_eq!(page.handle_input(&second_story_id.to_string()).unwrap(), Some(Action::NavigateToStoryDetail { epic_id: second_epic_id, story_id: second_story_id }));
+            assert_eq!(page.handle_input(&unplanned_story_id.to_string()).unwrap(), None);
+        }
+
+        #[test]
+        fn sprint_detail_should_offer_actions_for_its_state() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let sprint_id = db.create_sprint(sprint("sprint")).unwrap();
+
+            let page = SprintDetail { sprint_id, db: Rc::clone(&db) };
+
+            assert_eq!(page.handle_input("t").unwrap(), Some(Action::StartSprint { sprint_id }));
+            assert_eq!(page.handle_input("x").unwrap(), None);
+
+            db.start_sprint(sprint_id).unwrap();
+
+            assert_eq!(page.handle_input("t").unwrap(), None);
+            assert_eq!(page.handle_input("x").unwrap(), Some(Action::CloseSprint { sprint_id }));
+            assert_eq!(page.handle_input("p").unwrap(), Some(Action::NavigateToPreviousPage));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        }
+
+        #[test]
+        fn sprint_detail_should_throw_error_for_invalid_sprint_id() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+
+            let page = SprintDetail { sprint_id: 999, db };
+            assert!(page.draw_page().is_err());
+        }
+    }
+
+    #[test]
+    fn describe_progress_should_skip_empty_statuses() {
+        let progress = EpicProgress::new(&[Status::Open, Status::Closed, Status::Closed, Status::Closed]);
+
+        assert_eq!(describe_progress(&progress), "1 OPEN (25%) | 3 CLOSED (75%)");
+        assert_eq!(describe_progress(&EpicProgress::default()), "no stories");
+    }
+
+    mod board_page {
+        use super::*;
+
+        fn card_ids(columns: &[Vec<(u32, &Story)>]) -> Vec<Vec<u32>> {
+            columns.iter().map(|cards| cards.iter().map(|(id, _)| *id).collect()).collect()
+        }
+
+        #[test]
+        fn columns_should_group_stories_by_status() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let first_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let second_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let first_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), first_epic_id).unwrap();
+            let second_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), second_epic_id).unwrap();
+            let third_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), first_epic_id).unwrap();
+
+            db.restore_story_status(first_story_id, Status::InProgress).unwrap();
+            db.restore_story_status(second_story_id, Status::Closed).unwrap();
+
+            let db_state = db.read_db().unwrap();
+
+            let board = BoardPage::new(None, Rc::clone(&db));
+            assert_eq!(card_ids(&board.columns(&db_state).unwrap()), vec![vec![third_story_id], vec![first_story_id], vec![], vec![second_story_id]]);
+
+            let board = BoardPage::new(Some(first_epic_id), Rc::clone(&db));
+            assert_eq!(card_ids(&board.columns(&db_state).unwrap()), vec![vec![third_story_id], vec![first_story_id], vec![], vec![]]);
+            assert!(board.draw_page().is_ok());
+
+            assert!(BoardPage::new(Some(999), db).draw_page().is_err());
+        }
+
+        #[test]
+        fn handle_input_should_select_and_move_cards() {
+            let db = Rc::new(JiraDatabase::with_database(Box::new(MockDB::new())));
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("a very long story title that does not fit on a card".to_owned(), "".to_owned()), epic_id).unwrap();
+
+            let page = BoardPage::new(Some(epic_id), Rc::clone(&db));
+
+            // nothing to move before a card is selected
+            assert_eq!(page.handle_input(">").unwrap(), None);
+            assert_eq!(page.handle_input("999").unwrap(), None);
+            assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), None);
+            assert_eq!(*page.selected.borrow(), Some(story_id));
+            assert!(page.draw_page().is_ok());
+
+            assert_eq!(page.handle_input("<").unwrap(), None);
+            assert_eq!(page.handle_input(">").unwrap(), Some(Action::MoveCard { story_id, status: Status::InProgress }));
+            assert_eq!(page.handle_input("v").unwrap(), Some(Action::NavigateToStoryDetail { epic_id, story_id }));
+
+            db.restore_story_status(story_id, Status::Closed).unwrap();
+
+            assert_eq!(page.handle_input(">").unwrap(), None);
+            assert_eq!(page.handle_input("<").unwrap(), Some(Action::MoveCard { story_id, status: Status::Open }));
+            assert_eq!(page.handle_input("p").unwrap(), Some(Action::NavigateToPreviousPage));
+        }
+
+        #[test]
+        fn handle_input_should_move_cards_along_the_workflow() {
+            let mut db = JiraDatabase::with_database(Box::new(MockDB::new()));
+            db.workflow = Workflow::new().allow(Status::Resolved, Status::InProgress);
+
+            let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+            let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+            db.restore_story_status(story_id, Status::Resolved).unwrap();
+
+            let page = BoardPage::new(Some(epic_id), Rc::new(db));
+            page.handle_input(&story_id.to_string()).unwrap();
+
+            assert_eq!(page.handle_input("<").unwrap(), Some(Action::MoveCard { story_id, status: Status::InProgress }));
+            assert_eq!(page.handle_input(">").unwrap(), None);
+        }
+    }
+}
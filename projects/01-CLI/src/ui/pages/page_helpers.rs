@@ -1,33 +1,161 @@
-use ellipse::Ellipse;
+use itertools::Itertools;
+use terminal_size::{terminal_size, Width};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+use crate::models::{Change, Comment, Timestamp};
+
+// number of history entries shown on the detail pages
+pub const HISTORY_LENGTH: usize = 5;
+// number of comments shown on the detail pages
+pub const COMMENTS_LENGTH: usize = 5;
+
+const COLUMN_SEPARATOR: &str = " | ";
+
+// time, field, old value and new value
+const HISTORY_COLUMNS: [usize; 4] = [20, 12, 16, 16];
+// id, time, author and comment
+const COMMENT_COLUMNS: [usize; 4] = [4, 20, 12, 32];
+
+/// Pads or truncates `text` to exactly `width` terminal columns. Width is measured per grapheme
+/// cluster, so accented letters take one column and CJK characters or emoji take two.
 pub fn get_column_string(text: &str, width: usize) -> String {
-    //todo!() // use the truncate_ellipse function from the ellipse crate
-    let len = text.len();
-
-    match len.cmp(&width) {
-        std::cmp::Ordering::Equal => text.to_string(),
-        std::cmp::Ordering::Less => {
-            let mut column_string = text.to_string();
-            
-            for _ in 0..(width - len) {
-                column_string.push(' ');
-            }
-
-            column_string
-        },
-        std::cmp::Ordering::Greater => {
-            let mut column_string = "".to_owned();
-
-            if width <= 3 {
-                for _ in 0..width {
-                    column_string.push('.');
-                }
-            } else {
-                column_string = text.truncate_ellipse(width - 3).to_string();
-            }
-
-            column_string
-        },
+    let text_width = display_width(text);
+
+    if text_width <= width {
+        return format!("{}{}", text, " ".repeat(width - text_width));
+    }
+
+    if width <= 3 {
+        return ".".repeat(width);
+    }
+
+    let mut column_string = "".to_owned();
+    let mut used = 0;
+
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+
+        if used + grapheme_width > width - 3 {
+            break;
+        }
+
+        column_string.push_str(grapheme);
+        used += grapheme_width;
+    }
+
+    // a wide character that did not fit leaves a gap before the ellipsis is padded
+    format!("{}...{}", column_string, " ".repeat(width - 3 - used))
+}
+
+/// Number of terminal columns `text` takes up.
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(|grapheme| grapheme.width()).sum()
+}
+
+/// Scales the fixed column `widths` of a table so its rows fill the terminal. When stdout is not
+/// a terminal, e.g. when piped to a file, the fixed widths are returned unchanged.
+pub fn fit_columns(widths: &[usize]) -> Vec<usize> {
+    let terminal_width = terminal_size().map(|(Width(width), _)| width as usize);
+
+    scale_columns(widths, terminal_width)
+}
+
+fn scale_columns(widths: &[usize], terminal_width: Option<usize>) -> Vec<usize> {
+    let total: usize = widths.iter().sum();
+
+    let Some(terminal_width) = terminal_width.filter(|_| total > 0) else {
+        return widths.to_vec();
+    };
+
+    // one column is left free so that full rows do not wrap
+    let separators = COLUMN_SEPARATOR.len() * widths.len().saturating_sub(1);
+    let available = terminal_width.saturating_sub(separators + 1);
+
+    let mut scaled: Vec<usize> = widths.iter().map(|width| (width * available / total).max(1)).collect();
+
+    // rounding leftovers go to the widest column, which is usually the name
+    let used: usize = scaled.iter().sum();
+
+    if let Some((widest, _)) = widths.iter().enumerate().rev().max_by_key(|(_, width)| **width) {
+        scaled[widest] += available.saturating_sub(used);
+    }
+
+    scaled
+}
+
+/// Builds a table row out of `values`, each fitted to the matching width.
+pub fn get_row_string(values: &[&str], widths: &[usize]) -> String {
+    values.iter().zip(widths).map(|(value, width)| get_column_string(value, *width)).join(COLUMN_SEPARATOR)
+}
+
+/// Builds the header line of a table with every label centered above its column.
+pub fn get_header_string(labels: &[&str], widths: &[usize]) -> String {
+    let last = labels.len().saturating_sub(1);
+
+    labels.iter().zip(widths).enumerate().map(|(index, (label, width))| {
+        // inner cells also span the spaces around both separators
+        let cell_width = width + if index == 0 || index == last { 1 } else { 2 };
+
+        center(label, cell_width, ' ')
+    }).join("|")
+}
+
+/// Builds a section title such as "---- EPICS ----" as wide as a table with the given column widths.
+pub fn get_title_string(title: &str, widths: &[usize]) -> String {
+    let table_width = widths.iter().sum::<usize>() + COLUMN_SEPARATOR.len() * widths.len().saturating_sub(1);
+
+    center(&format!(" {} ", title), table_width, '-')
+}
+
+fn center(text: &str, width: usize, fill: char) -> String {
+    let text = if display_width(text) > width { get_column_string(text, width) } else { text.to_owned() };
+    let padding = width - display_width(&text);
+    let left = padding / 2;
+
+    format!("{}{}{}", fill.to_string().repeat(left), text, fill.to_string().repeat(padding - left))
+}
+
+/// Prints the creation and update times followed by the latest changes, newest first.
+pub fn draw_history(created_at: Option<Timestamp>, updated_at: Option<Timestamp>, history: &[Change]) {
+    let format_time = |time: Option<Timestamp>| time.map_or("unknown".to_owned(), |time| time.to_string());
+
+    println!("created: {} | updated: {}", format_time(created_at), format_time(updated_at));
+    println!();
+
+    let widths = fit_columns(&HISTORY_COLUMNS);
+
+    println!("{}", get_title_string("HISTORY", &widths));
+    println!("{}", get_header_string(&["time", "field", "old", "new"], &widths));
+
+    for change in history.iter().rev().take(HISTORY_LENGTH) {
+        let old_value = if change.old_value.is_empty() { "-" } else { &change.old_value };
+        let new_value = if change.new_value.is_empty() { "-" } else { &change.new_value };
+        println!("{}", get_row_string(&[&change.changed_at.to_string(), &change.field, old_value, new_value], &widths));
+    }
+}
+
+/// Prints the latest comments, oldest first so the thread reads top to bottom.
+pub fn draw_comments(comments: &[Comment]) {
+    let widths = fit_columns(&COMMENT_COLUMNS);
+
+    println!("{}", get_title_string("COMMENTS", &widths));
+    println!("{}", get_header_string(&["id", "time", "author", "comment"], &widths));
+
+    let hidden = comments.len().saturating_sub(COMMENTS_LENGTH);
+
+    if hidden > 0 {
+        println!("({} earlier comments not shown)", hidden);
+    }
+
+    for comment in &comments[hidden..] {
+        // edited comments show when they were last changed
+        let time = match comment.edited_at {
+            Some(edited_at) => format!("{} *", edited_at),
+            None => comment.created_at.to_string()
+        };
+
+        println!("{}", get_row_string(&[&comment.id.to_string(), &time, &comment.author, &comment.body], &widths));
     }
 }
 
@@ -69,4 +197,50 @@ mod tests {
         assert_eq!(get_column_string(text3, width), "testme".to_owned());
         assert_eq!(get_column_string(text4, width), "tes...".to_owned());
     } 
+
+    #[test]
+    fn get_column_string_should_measure_display_width() {
+        // a precomposed and a decomposed accent both take one column
+        assert_eq!(get_column_string("café", 6), "café  ");
+        assert_eq!(get_column_string("cafe\u{301}", 6), "cafe\u{301}  ");
+
+        // CJK characters and emoji take two columns
+        assert_eq!(get_column_string("日本", 6), "日本  ");
+        assert_eq!(get_column_string("🚀 go", 6), "🚀 go ");
+
+        assert_eq!(get_column_string("日本語のテキスト", 8), "日本... ");
+        assert_eq!(get_column_string("naïve résumé", 8), "naïve...");
+        assert_eq!(display_width(&get_column_string("👩‍💻👩‍💻👩‍💻", 5)), 5);
+    }
+
+    #[test]
+    fn scale_columns_should_keep_fixed_widths_without_terminal() {
+        assert_eq!(scale_columns(&[11, 32, 17], None), vec![11, 32, 17]);
+    }
+
+    #[test]
+    fn scale_columns_should_fill_terminal_width() {
+        let widths = scale_columns(&[11, 32, 17], Some(127));
+
+        assert_eq!(widths.iter().sum::<usize>() + 2 * COLUMN_SEPARATOR.len(), 126);
+        assert_eq!(widths, vec![22, 64, 34]);
+
+        let widths = scale_columns(&[11, 32, 17], Some(40));
+
+        assert_eq!(widths.iter().sum::<usize>() + 2 * COLUMN_SEPARATOR.len(), 39);
+        assert!(widths[1] > widths[2] && widths[2] > widths[0]);
+    }
+
+    #[test]
+    fn get_header_string_should_center_labels_over_columns() {
+        assert_eq!(get_header_string(&["id", "name", "status"], &[11, 32, 17]),
+            "     id     |               name               |      status      ");
+        assert_eq!(get_header_string(&["id", "name", "status"], &[11, 32, 17]).len(),
+            get_row_string(&["1", "name", "OPEN"], &[11, 32, 17]).len());
+    }
+
+    #[test]
+    fn get_title_string_should_span_the_table() {
+        assert_eq!(get_title_string("EPIC", &[4, 4]), "-- EPIC ---");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,875 @@
+use std::env;
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::models::{ChecklistItem, Comment, Date, DetailsUpdate, Epic, LinkType, Priority, Sprint, Status, Story, StoryLink, StoryPlanning};
+use crate::ui::get_column_string;
+
+// receives the statuses the item is allowed to move to
+type StatusPrompt = Box<dyn Fn(&[Status]) -> Result<Option<Status>>>;
+// receives the current name and description
+type DetailsPrompt = Box<dyn Fn(&str, &str) -> Result<DetailsUpdate>>;
+// receives the (id, name) of every epic the story can be moved to
+type MoveStoryPrompt = Box<dyn Fn(&[(u32, String)]) -> Result<Option<u32>>>;
+// receives the current planning of the story
+type PlanningPrompt = Box<dyn Fn(&StoryPlanning) -> Result<StoryPlanning>>;
+// returns the author and body of the new comment
+type AddCommentPrompt = Box<dyn Fn() -> Result<Option<(String, String)>>>;
+// receives the comments to choose from
+type ChooseCommentPrompt = Box<dyn Fn(&[Comment]) -> Result<Option<u32>>>;
+// receives the current body of the comment
+type EditCommentPrompt = Box<dyn Fn(&str) -> Result<Option<String>>>;
+// receives the checklist and returns the position of the chosen item
+type ChooseChecklistItemPrompt = Box<dyn Fn(&[ChecklistItem]) -> Result<Option<usize>>>;
+// receives the number of items and returns the new position
+type MoveChecklistItemPrompt = Box<dyn Fn(usize) -> Result<Option<usize>>>;
+// receives the id of the story being linked and the (id, name) of every other story
+type LinkStoryPrompt = Box<dyn Fn(u32, &[(u32, String)]) -> Result<Option<StoryLink>>>;
+// receives the id of the story and its links
+type UnlinkStoryPrompt = Box<dyn Fn(u32, &[StoryLink]) -> Result<Option<StoryLink>>>;
+// receives the ids of the open stories that block the one being closed
+type OpenBlockersPrompt = Box<dyn Fn(&[u32]) -> Result<bool>>;
+// receives the (id, name) of every sprint the story can be planned into, returns Some(None) for the backlog
+type AssignSprintPrompt = Box<dyn Fn(&[(u32, String)]) -> Result<Option<Option<u32>>>>;
+// receives the number of unfinished stories and the (id, name) of every planned sprint they can move to
+type CloseSprintPrompt = Box<dyn Fn(usize, &[(u32, String)]) -> Result<Option<Option<u32>>>>;
+
+pub struct Prompts {
+    pub search: Box<dyn Fn() -> Result<String>>,
+    pub create_epic: Box<dyn Fn() -> Result<Epic>>,
+    pub create_story: Box<dyn Fn() -> Result<Story>>,
+    pub delete_epic: Box<dyn Fn() -> Result<bool>>,
+    pub delete_story: Box<dyn Fn() -> Result<bool>>,
+    pub update_status: StatusPrompt,
+    pub update_details: DetailsPrompt,
+    pub move_story: MoveStoryPrompt,
+    pub update_planning: PlanningPrompt,
+    pub add_comment: AddCommentPrompt,
+    pub choose_comment: ChooseCommentPrompt,
+    pub edit_comment: EditCommentPrompt,
+    pub add_checklist_item: Box<dyn Fn() -> Result<Option<String>>>,
+    pub choose_checklist_item: ChooseChecklistItemPrompt,
+    pub move_checklist_item: MoveChecklistItemPrompt,
+    // receives the number of open checklist items
+    pub resolve_with_open_items: Box<dyn Fn(usize) -> Result<bool>>,
+    pub link_story: LinkStoryPrompt,
+    pub unlink_story: UnlinkStoryPrompt,
+    pub close_with_open_blockers: OpenBlockersPrompt,
+    pub create_sprint: Box<dyn Fn() -> Result<Sprint>>,
+    pub assign_sprint: AssignSprintPrompt,
+    pub close_sprint: CloseSprintPrompt
+}
+
+impl Prompts {
+    pub fn new() -> Self {
+        Self {
+            search: Box::new(|| search_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            create_epic: Box::new(|| create_epic_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            create_story: Box::new(|| create_story_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            delete_epic: Box::new(|| delete_epic_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            delete_story: Box::new(|| delete_story_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            update_status: Box::new(|options| update_status_prompt(&mut io::stdin().lock(), &mut io::stdout(), options)),
+            update_details: Box::new(|name, description| update_details_prompt(&mut io::stdin().lock(), &mut io::stdout(), name, description)),
+            move_story: Box::new(|epics| move_story_prompt(&mut io::stdin().lock(), &mut io::stdout(), epics)),
+            update_planning: Box::new(|planning| update_planning_prompt(&mut io::stdin().lock(), &mut io::stdout(), planning)),
+            add_comment: Box::new(|| add_comment_prompt(&mut io::stdin().lock(), &mut io::stdout(), default_author().as_deref())),
+            choose_comment: Box::new(|comments| choose_comment_prompt(&mut io::stdin().lock(), &mut io::stdout(), comments)),
+            edit_comment: Box::new(|body| edit_comment_prompt(&mut io::stdin().lock(), &mut io::stdout(), body)),
+            add_checklist_item: Box::new(|| add_checklist_item_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            choose_checklist_item: Box::new(|checklist| choose_checklist_item_prompt(&mut io::stdin().lock(), &mut io::stdout(), checklist)),
+            move_checklist_item: Box::new(|count| move_checklist_item_prompt(&mut io::stdin().lock(), &mut io::stdout(), count)),
+            resolve_with_open_items: Box::new(|open_items| resolve_with_open_items_prompt(&mut io::stdin().lock(), &mut io::stdout(), open_items)),
+            link_story: Box::new(|story_id, stories| link_story_prompt(&mut io::stdin().lock(), &mut io::stdout(), story_id, stories)),
+            unlink_story: Box::new(|story_id, links| unlink_story_prompt(&mut io::stdin().lock(), &mut io::stdout(), story_id, links)),
+            close_with_open_blockers: Box::new(|blockers| close_with_open_blockers_prompt(&mut io::stdin().lock(), &mut io::stdout(), blockers)),
+            create_sprint: Box::new(|| create_sprint_prompt(&mut io::stdin().lock(), &mut io::stdout())),
+            assign_sprint: Box::new(|sprints| assign_sprint_prompt(&mut io::stdin().lock(), &mut io::stdout(), sprints)),
+            close_sprint: Box::new(|unfinished, sprints| close_sprint_prompt(&mut io::stdin().lock(), &mut io::stdout(), unfinished, sprints))
+        }
+    }
+}
+
+/// Asks for a search query. An empty query means the user cancelled.
+pub fn search_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<String> {
+    writeln!(writer, "----------------------------")?;
+
+    read_line(reader, writer, "Search epics and stories (empty to cancel):")
+}
+
+pub fn create_epic_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<Epic> {
+    writeln!(writer, "----------------------------")?;
+
+    let name = read_required_line(reader, writer, "Epic Name:")?;
+    let description = read_line(reader, writer, "Epic Description:")?;
+
+    Ok(Epic::new(name, description))
+}
+
+pub fn create_story_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<Story> {
+    writeln!(writer, "----------------------------")?;
+
+    let name = read_required_line(reader, writer, "Story Name:")?;
+    let description = read_line(reader, writer, "Story Description:")?;
+
+    Ok(Story::new(name, description))
+}
+
+pub fn delete_epic_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<bool> {
+    writeln!(writer, "----------------------------")?;
+
+    read_confirmation(reader, writer, "Are you sure you want to delete this epic? All stories in this epic will also be deleted [y/N]:")
+}
+
+pub fn delete_story_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<bool> {
+    writeln!(writer, "----------------------------")?;
+
+    read_confirmation(reader, writer, "Are you sure you want to delete this story? [y/N]:")
+}
+
+/// Asks for one of `options` by number. Returns `None` if the user leaves the
+/// input empty, and asks again on any number that is not listed.
+pub fn update_status_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, options: &[Status]) -> Result<Option<Status>> {
+    writeln!(writer, "----------------------------")?;
+
+    if options.is_empty() {
+        writeln!(writer, "The status can't be changed from here.")?;
+        return Ok(None);
+    }
+
+    let choices = options.iter().enumerate().map(|(index, status)| format!("{} - {}", index + 1, status)).collect::<Vec<_>>().join(", ");
+
+    loop {
+        let input = read_line(reader, writer, &format!("New Status ({}) or empty to cancel:", choices))?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match input.parse::<usize>() {
+            Ok(number) if (1..=options.len()).contains(&number) => return Ok(Some(options[number - 1].clone())),
+            _ => writeln!(writer, "Invalid status \"{}\", please enter a number between 1 and {}.", input, options.len())?
+        }
+    }
+}
+
+/// Asks for a new name and description, showing the current values.
+/// Leaving an input empty keeps the current value.
+pub fn update_details_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, name: &str, description: &str) -> Result<DetailsUpdate> {
+    writeln!(writer, "----------------------------")?;
+
+    let new_name = read_line(reader, writer, &format!("Name [{}] or empty to keep:", name))?;
+    let new_description = read_line(reader, writer, &format!("Description [{}] or empty to keep:", description))?;
+
+    Ok(DetailsUpdate {
+        name: Some(new_name).filter(|new_name| !new_name.is_empty()),
+        description: Some(new_description).filter(|new_description| !new_description.is_empty())
+    })
+}
+
+/// Asks for the id of one of `epics`. Returns `None` if the user leaves the
+/// input empty, and asks again on any id that is not listed.
+pub fn move_story_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, epics: &[(u32, String)]) -> Result<Option<u32>> {
+    writeln!(writer, "----------------------------")?;
+
+    if epics.is_empty() {
+        writeln!(writer, "There is no other epic to move this story to.")?;
+        return Ok(None);
+    }
+
+    for (id, name) in epics {
+        writeln!(writer, "{} - {}", id, name)?;
+    }
+
+    loop {
+        let input = read_line(reader, writer, "Move story to epic (id) or empty to cancel:")?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match input.parse::<u32>() {
+            Ok(epic_id) if epics.iter().any(|(id, _)| *id == epic_id) => return Ok(Some(epic_id)),
+            _ => writeln!(writer, "Invalid epic \"{}\", please enter one of the ids above.", input)?
+        }
+    }
+}
+
+/// Asks for each planning field of a story, showing the current values.
+/// Leaving an input empty keeps the current value and "-" clears an optional one.
+pub fn update_planning_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, planning: &StoryPlanning) -> Result<StoryPlanning> {
+    writeln!(writer, "----------------------------")?;
+
+    let assignee = read_optional_field(reader, writer, "Assignee", planning.assignee.clone())?;
+
+    let choices = Priority::ALL.iter().enumerate().map(|(index, priority)| format!("{} - {}", index + 1, priority)).collect::<Vec<_>>().join(", ");
+
+    let priority = loop {
+        let input = read_line(reader, writer, &format!("Priority [{}] ({}) or empty to keep:", planning.priority, choices))?;
+
+        if input.is_empty() {
+            break planning.priority.clone();
+        }
+
+        match input.parse::<usize>() {
+            Ok(number) if (1..=Priority::ALL.len()).contains(&number) => break Priority::ALL[number - 1].clone(),
+            _ => writeln!(writer, "Invalid priority \"{}\", please enter a number between 1 and {}.", input, Priority::ALL.len())?
+        }
+    };
+
+    let story_points = read_optional_field(reader, writer, "Story Points", planning.story_points)?;
+    let due_date = read_optional_field(reader, writer, "Due Date (YYYY-MM-DD)", planning.due_date)?;
+
+    Ok(StoryPlanning { assignee, priority, story_points, due_date })
+}
+
+/// Asks for the author and text of a new comment. The author defaults to `default_author`
+/// when there is one. Returns `None` if the user leaves the comment empty.
+pub fn add_comment_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, default_author: Option<&str>) -> Result<Option<(String, String)>> {
+    writeln!(writer, "----------------------------")?;
+
+    let author = match default_author {
+        Some(default_author) => Some(read_line(reader, writer, &format!("Author [{}] or empty to keep:", default_author))?)
+            .filter(|author| !author.is_empty())
+            .unwrap_or_else(|| default_author.to_owned()),
+        None => read_required_line(reader, writer, "Author:")?
+    };
+
+    let body = read_line(reader, writer, "Comment (empty to cancel):")?;
+
+    Ok(Some((author, body)).filter(|(_, body)| !body.is_empty()))
+}
+
+/// Asks for the id of one of `comments`. Returns `None` if the user leaves the
+/// input empty, and asks again on any id that is not listed.
+pub fn choose_comment_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, comments: &[Comment]) -> Result<Option<u32>> {
+    writeln!(writer, "----------------------------")?;
+
+    if comments.is_empty() {
+        writeln!(writer, "There are no comments yet.")?;
+        return Ok(None);
+    }
+
+    for comment in comments {
+        writeln!(writer, "{} - {}: {}", comment.id, comment.author, get_column_string(&comment.body, 40).trim_end())?;
+    }
+
+    loop {
+        let input = read_line(reader, writer, "Comment (id) or empty to cancel:")?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match input.parse::<u32>() {
+            Ok(comment_id) if comments.iter().any(|comment| comment.id == comment_id) => return Ok(Some(comment_id)),
+            _ => writeln!(writer, "Invalid comment \"{}\", please enter one of the ids above.", input)?
+        }
+    }
+}
+
+/// Asks for the new text of a comment, showing the current one.
+/// Returns `None` if the user leaves the input empty.
+pub fn edit_comment_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, body: &str) -> Result<Option<String>> {
+    writeln!(writer, "----------------------------")?;
+
+    let new_body = read_line(reader, writer, &format!("Comment [{}] or empty to keep:", body))?;
+
+    Ok(Some(new_body).filter(|new_body| !new_body.is_empty()))
+}
+
+/// Asks for the text of a new checklist item. Returns `None` if the user leaves it empty.
+pub fn add_checklist_item_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<Option<String>> {
+    writeln!(writer, "----------------------------")?;
+
+    let text = read_line(reader, writer, "Checklist Item (empty to cancel):")?;
+
+    Ok(Some(text).filter(|text| !text.is_empty()))
+}
+
+/// Asks for one of the `checklist` items by number and returns its position.
+/// Returns `None` if the user leaves the input empty.
+pub fn choose_checklist_item_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, checklist: &[ChecklistItem]) -> Result<Option<usize>> {
+    writeln!(writer, "----------------------------")?;
+
+    if checklist.is_empty() {
+        writeln!(writer, "The checklist is empty.")?;
+        return Ok(None);
+    }
+
+    for (index, item) in checklist.iter().enumerate() {
+        writeln!(writer, "{} - [{}] {}", index + 1, if item.done { "x" } else { " " }, item.text)?;
+    }
+
+    read_position(reader, writer, "Checklist Item", checklist.len())
+}
+
+/// Asks for the new position of a checklist item among `count` items.
+/// Returns `None` if the user leaves the input empty.
+pub fn move_checklist_item_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, count: usize) -> Result<Option<usize>> {
+    writeln!(writer, "----------------------------")?;
+
+    read_position(reader, writer, "New Position", count)
+}
+
+pub fn resolve_with_open_items_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, open_items: usize) -> Result<bool> {
+    writeln!(writer, "----------------------------")?;
+
+    read_confirmation(reader, writer, &format!("This story still has {} open checklist item(s). Resolve it anyway? [y/N]:", open_items))
+}
+
+/// Asks how story `story_id` relates to one of `stories` and builds the link.
+/// Returns `None` if the user leaves either input empty.
+pub fn link_story_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, story_id: u32, stories: &[(u32, String)]) -> Result<Option<StoryLink>> {
+    writeln!(writer, "----------------------------")?;
+
+    if stories.is_empty() {
+        writeln!(writer, "There is no other story to link to.")?;
+        return Ok(None);
+    }
+
+    // "is blocked by" creates a "blocks" link pointing the other way
+    let kinds = ["blocks", "is blocked by", "relates to"];
+    let choices = kinds.iter().enumerate().map(|(index, kind)| format!("{} - {}", index + 1, kind)).collect::<Vec<_>>().join(", ");
+
+    let Some(kind) = read_position(reader, writer, &format!("Link Type ({})", choices), kinds.len())? else {
+        return Ok(None);
+    };
+
+    for (id, name) in stories {
+        writeln!(writer, "{} - {}", id, name)?;
+    }
+
+    loop {
+        let input = read_line(reader, writer, &format!("This story {} story (id) or empty to cancel:", kinds[kind]))?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match input.parse::<u32>() {
+            Ok(other_id) if stories.iter().any(|(id, _)| *id == other_id) => return Ok(Some(match kind {
+                0 => StoryLink { from: story_id, to: other_id, link_type: LinkType::Blocks },
+                1 => StoryLink { from: other_id, to: story_id, link_type: LinkType::Blocks },
+                _ => StoryLink { from: story_id, to: other_id, link_type: LinkType::RelatesTo },
+            })),
+            _ => writeln!(writer, "Invalid story \"{}\", please enter one of the ids above.", input)?
+        }
+    }
+}
+
+/// Asks for one of the `links` of story `story_id` by number.
+/// Returns `None` if the user leaves the input empty.
+pub fn unlink_story_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, story_id: u32, links: &[StoryLink]) -> Result<Option<StoryLink>> {
+    writeln!(writer, "----------------------------")?;
+
+    if links.is_empty() {
+        writeln!(writer, "This story has no links.")?;
+        return Ok(None);
+    }
+
+    for (index, link) in links.iter().enumerate() {
+        let (label, other_id) = link.seen_from(story_id);
+        writeln!(writer, "{} - {} story {}", index + 1, label, other_id)?;
+    }
+
+    Ok(read_position(reader, writer, "Link", links.len())?.map(|position| links[position].clone()))
+}
+
+pub fn close_with_open_blockers_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, blockers: &[u32]) -> Result<bool> {
+    writeln!(writer, "----------------------------")?;
+
+    let blockers = blockers.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ");
+
+    read_confirmation(reader, writer, &format!("This story is still blocked by open stories {}. Close it anyway? [y/N]:", blockers))
+}
+
+/// Asks for the name, goal and dates of a new sprint, asking again until the end date is not before the start date.
+pub fn create_sprint_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> Result<Sprint> {
+    writeln!(writer, "----------------------------")?;
+
+    let name = read_required_line(reader, writer, "Sprint Name:")?;
+    let goal = read_line(reader, writer, "Sprint Goal:")?;
+    let start_date = read_date(reader, writer, "Start Date (YYYY-MM-DD):")?;
+
+    let end_date = loop {
+        let end_date = read_date(reader, writer, "End Date (YYYY-MM-DD):")?;
+
+        if end_date >= start_date {
+            break end_date;
+        }
+
+        writeln!(writer, "The sprint cannot end before it starts ({}).", start_date)?;
+    };
+
+    Ok(Sprint::new(name, goal, start_date, end_date))
+}
+
+/// Asks for the sprint to plan a story into. "-" takes the story back to the backlog
+/// and empty input cancels, which is returned as `None`.
+pub fn assign_sprint_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, sprints: &[(u32, String)]) -> Result<Option<Option<u32>>> {
+    writeln!(writer, "----------------------------")?;
+
+    read_sprint(reader, writer, "Plan story into sprint (id), \"-\" for the backlog or empty to cancel:", sprints)
+}
+
+/// Asks where the unfinished stories of the sprint being closed should go, or just
+/// for confirmation when there are none. Returns `None` if the user cancels.
+pub fn close_sprint_prompt<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, unfinished: usize, sprints: &[(u32, String)]) -> Result<Option<Option<u32>>> {
+    writeln!(writer, "----------------------------")?;
+
+    if unfinished == 0 {
+        let confirmed = read_confirmation(reader, writer, "All stories of this sprint are finished. Close it? [y/N]:")?;
+        return Ok(confirmed.then_some(None));
+    }
+
+    writeln!(writer, "{} unfinished stories will leave this sprint.", unfinished)?;
+
+    read_sprint(reader, writer, "Move them to sprint (id), \"-\" for the backlog or empty to cancel:", sprints)
+}
+
+// lists `sprints` and reads one of their ids, "-" for none
+fn read_sprint<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, message: &str, sprints: &[(u32, String)]) -> Result<Option<Option<u32>>> {
+    for (id, name) in sprints {
+        writeln!(writer, "{} - {}", id, name)?;
+    }
+
+    loop {
+        let input = read_line(reader, writer, message)?;
+
+        match input.as_str() {
+            "" => return Ok(None),
+            "-" => return Ok(Some(None)),
+            _ => match input.parse::<u32>() {
+                Ok(sprint_id) if sprints.iter().any(|(id, _)| *id == sprint_id) => return Ok(Some(Some(sprint_id))),
+                _ => writeln!(writer, "Invalid sprint \"{}\", please enter one of the ids above.", input)?
+            }
+        }
+    }
+}
+
+fn read_date<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, message: &str) -> Result<Date> {
+    loop {
+        let input = read_line(reader, writer, message)?;
+
+        match input.parse() {
+            Ok(date) => return Ok(date),
+            Err(error) => writeln!(writer, "{}.", error)?
+        }
+    }
+}
+
+// reads a number between 1 and `count` and returns it as a zero based position
+fn read_position<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, name: &str, count: usize) -> Result<Option<usize>> {
+    loop {
+        let input = read_line(reader, writer, &format!("{} (1-{}) or empty to cancel:", name, count))?;
+
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        match input.parse::<usize>() {
+            Ok(number) if (1..=count).contains(&number) => return Ok(Some(number - 1)),
+            _ => writeln!(writer, "Invalid position \"{}\", please enter a number between 1 and {}.", input, count)?
+        }
+    }
+}
+
+// the login name of the current user, if the environment has one
+fn default_author() -> Option<String> {
+    env::var("USER").or_else(|_| env::var("USERNAME")).ok().filter(|author| !author.is_empty())
+}
+
+fn read_line<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, message: &str) -> Result<String> {
+    writeln!(writer, "{}", message)?;
+    writer.flush()?;
+
+    let mut input = String::new();
+
+    if reader.read_line(&mut input)? == 0 {
+        return Err(anyhow!("unexpected end of input"));
+    }
+
+    Ok(input.trim().to_owned())
+}
+
+fn read_required_line<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, message: &str) -> Result<String> {
+    loop {
+        let input = read_line(reader, writer, message)?;
+
+        if !input.is_empty() {
+            return Ok(input);
+        }
+
+        writeln!(writer, "This field cannot be empty.")?;
+    }
+}
+
+// empty input keeps `current`, "-" clears it and anything else must parse as a `T`
+fn read_optional_field<R: BufRead, W: Write, T: FromStr + Display>(reader: &mut R, writer: &mut W, name: &str, current: Option<T>) -> Result<Option<T>> {
+    let current_text = current.as_ref().map_or("-".to_owned(), |value| value.to_string());
+
+    loop {
+        let input = read_line(reader, writer, &format!("{} [{}] or empty to keep, \"-\" to clear:", name, current_text))?;
+
+        match input.as_str() {
+            "" => return Ok(current),
+            "-" => return Ok(None),
+            _ => match input.parse() {
+                Ok(value) => return Ok(Some(value)),
+                Err(_) => writeln!(writer, "Invalid value \"{}\" for {}.", input, name)?
+            }
+        }
+    }
+}
+
+fn read_confirmation<R: BufRead, W: Write>(reader: &mut R, writer: &mut W, message: &str) -> Result<bool> {
+    loop {
+        let input = read_line(reader, writer, message)?;
+
+        match input.to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "" | "n" | "no" => return Ok(false),
+            _ => writeln!(writer, "Please answer \"y\" or \"n\".")?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::models::Timestamp;
+
+    use super::*;
+
+    fn run<T>(input: &str, prompt: fn(&mut Cursor<Vec<u8>>, &mut Vec<u8>) -> Result<T>) -> (Result<T>, String) {
+        let mut reader = Cursor::new(input.as_bytes().to_vec());
+        let mut writer = Vec::new();
+
+        let result = prompt(&mut reader, &mut writer);
+
+        (result, String::from_utf8(writer).unwrap())
+    }
+
+    #[test]
+    fn create_epic_prompt_should_read_name_and_description() {
+        let (result, _) = run("  epic name \nepic description\n", create_epic_prompt);
+
+        assert_eq!(result.unwrap(), Epic::new("epic name".to_owned(), "epic description".to_owned()));
+    }
+
+    #[test]
+    fn create_epic_prompt_should_ask_again_for_empty_name() {
+        let (result, output) = run("\n   \nepic name\n\n", create_epic_prompt);
+
+        assert_eq!(result.unwrap(), Epic::new("epic name".to_owned(), "".to_owned()));
+        assert_eq!(output.matches("This field cannot be empty.").count(), 2);
+    }
+
+    #[test]
+    fn create_story_prompt_should_read_name_and_description() {
+        let (result, _) = run("story name\nstory description\n", create_story_prompt);
+
+        assert_eq!(result.unwrap(), Story::new("story name".to_owned(), "story description".to_owned()));
+    }
+
+    #[test]
+    fn create_story_prompt_should_fail_on_end_of_input() {
+        let (result, _) = run("story name\n", create_story_prompt);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn delete_prompts_should_read_confirmation() {
+        assert!(run("y\n", delete_epic_prompt).0.unwrap());
+        assert!(run("YES\n", delete_epic_prompt).0.unwrap());
+        assert!(!run("n\n", delete_epic_prompt).0.unwrap());
+        assert!(!run("\n", delete_epic_prompt).0.unwrap());
+
+        assert!(run("Y\n", delete_story_prompt).0.unwrap());
+        assert!(!run("no\n", delete_story_prompt).0.unwrap());
+    }
+
+    #[test]
+    fn delete_prompts_should_ask_again_for_unknown_answer() {
+        let (result, output) = run("maybe\ny\n", delete_story_prompt);
+
+        assert!(result.unwrap());
+        assert!(output.contains("Please answer \"y\" or \"n\"."));
+    }
+
+    fn update_all_statuses_prompt(reader: &mut Cursor<Vec<u8>>, writer: &mut Vec<u8>) -> Result<Option<Status>> {
+        update_status_prompt(reader, writer, &Status::ALL)
+    }
+
+    #[test]
+    fn update_status_prompt_should_map_numbers_to_statuses() {
+        assert_eq!(run("1\n", update_all_statuses_prompt).0.unwrap(), Some(Status::Open));
+        assert_eq!(run("2\n", update_all_statuses_prompt).0.unwrap(), Some(Status::InProgress));
+        assert_eq!(run("3\n", update_all_statuses_prompt).0.unwrap(), Some(Status::Resolved));
+        assert_eq!(run("4\n", update_all_statuses_prompt).0.unwrap(), Some(Status::Closed));
+    }
+
+    #[test]
+    fn update_status_prompt_should_return_none_for_empty_input() {
+        assert_eq!(run("\n", update_all_statuses_prompt).0.unwrap(), None);
+    }
+
+    #[test]
+    fn update_status_prompt_should_reject_invalid_numbers() {
+        let (result, output) = run("0\n5\nj983f2j\n3\n", update_all_statuses_prompt);
+
+        assert_eq!(result.unwrap(), Some(Status::Resolved));
+        assert_eq!(output.matches("Invalid status").count(), 3);
+    }
+
+    #[test]
+    fn update_status_prompt_should_fail_on_end_of_input() {
+        let (result, _) = run("9\n", update_all_statuses_prompt);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn update_status_prompt_should_only_offer_given_statuses() {
+        let (result, output) = run("3\n2\n", |reader, writer| update_status_prompt(reader, writer, &[Status::Open, Status::Closed]));
+
+        assert_eq!(result.unwrap(), Some(Status::Closed));
+        assert!(output.contains("(1 - OPEN, 2 - CLOSED)"));
+        assert!(!output.contains("IN PROGRESS"));
+    }
+
+    #[test]
+    fn update_status_prompt_should_return_none_without_options() {
+        let (result, _) = run("", |reader, writer| update_status_prompt(reader, writer, &[]));
+
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn update_details_prompt_should_keep_empty_fields() {
+        let (result, output) = run("new name\n\n", |reader, writer| update_details_prompt(reader, writer, "name", "description"));
+
+        assert_eq!(result.unwrap(), DetailsUpdate { name: Some("new name".to_owned()), description: None });
+        assert!(output.contains("Name [name]"));
+        assert!(output.contains("Description [description]"));
+    }
+
+    #[test]
+    fn update_details_prompt_should_read_both_fields() {
+        let (result, _) = run("  \nnew description\n", |reader, writer| update_details_prompt(reader, writer, "name", ""));
+
+        assert_eq!(result.unwrap(), DetailsUpdate { name: None, description: Some("new description".to_owned()) });
+    }
+
+    #[test]
+    fn move_story_prompt_should_only_accept_listed_epics() {
+        let (result, output) = run("2\nfour\n4\n", |reader, writer| move_story_prompt(reader, writer, &[(1, "first".to_owned()), (4, "second".to_owned())]));
+
+        assert_eq!(result.unwrap(), Some(4));
+        assert!(output.contains("4 - second"));
+        assert_eq!(output.matches("Invalid epic").count(), 2);
+    }
+
+    #[test]
+    fn move_story_prompt_should_return_none_for_empty_input() {
+        assert_eq!(run("\n", |reader, writer| move_story_prompt(reader, writer, &[(1, "first".to_owned())])).0.unwrap(), None);
+        assert_eq!(run("", |reader, writer| move_story_prompt(reader, writer, &[])).0.unwrap(), None);
+    }
+
+    #[test]
+    fn update_planning_prompt_should_read_all_fields() {
+        let (result, _) = run("alice\n3\n5\n2024-06-30\n", |reader, writer| update_planning_prompt(reader, writer, &StoryPlanning::default()));
+
+        assert_eq!(result.unwrap(), StoryPlanning {
+            assignee: Some("alice".to_owned()),
+            priority: Priority::High,
+            story_points: Some(5),
+            due_date: Some("2024-06-30".parse().unwrap())
+        });
+    }
+
+    #[test]
+    fn update_planning_prompt_should_keep_and_clear_fields() {
+        let planning = StoryPlanning {
+            assignee: Some("alice".to_owned()),
+            priority: Priority::Low,
+            story_points: Some(3),
+            due_date: Some("2024-06-30".parse().unwrap())
+        };
+
+        let (result, output) = run("\n\n-\n\n", |reader, writer| {
+            update_planning_prompt(reader, writer, &StoryPlanning {
+                assignee: Some("alice".to_owned()),
+                priority: Priority::Low,
+                story_points: Some(3),
+                due_date: Some("2024-06-30".parse().unwrap())
+            })
+        });
+
+        assert_eq!(result.unwrap(), StoryPlanning { story_points: None, ..planning });
+        assert!(output.contains("Assignee [alice]"));
+        assert!(output.contains("Priority [LOW]"));
+    }
+
+    #[test]
+    fn update_planning_prompt_should_ask_again_for_invalid_values() {
+        let (result, output) = run("\n9\n2\nfive\n5\n2024-02-30\n2024-02-29\n", |reader, writer| update_planning_prompt(reader, writer, &StoryPlanning::default()));
+
+        let planning = result.unwrap();
+
+        assert_eq!(planning.priority, Priority::Medium);
+        assert_eq!(planning.story_points, Some(5));
+        assert_eq!(planning.due_date, Some("2024-02-29".parse().unwrap()));
+        assert_eq!(output.matches("Invalid").count(), 3);
+    }
+
+    #[test]
+    fn search_prompt_should_read_query() {
+        assert_eq!(run("  login page \n", search_prompt).0.unwrap(), "login page");
+    }
+
+    #[test]
+    fn add_comment_prompt_should_default_author() {
+        let (result, output) = run("\n looks good \n", |reader, writer| add_comment_prompt(reader, writer, Some("ana")));
+
+        assert_eq!(result.unwrap(), Some(("ana".to_owned(), "looks good".to_owned())));
+        assert!(output.contains("Author [ana]"));
+
+        let (result, _) = run("\nbob\nlooks good\n", |reader, writer| add_comment_prompt(reader, writer, None));
+
+        assert_eq!(result.unwrap(), Some(("bob".to_owned(), "looks good".to_owned())));
+    }
+
+    #[test]
+    fn add_comment_prompt_should_return_none_for_empty_comment() {
+        assert_eq!(run("bob\n\n", |reader, writer| add_comment_prompt(reader, writer, Some("ana"))).0.unwrap(), None);
+    }
+
+    #[test]
+    fn choose_comment_prompt_should_only_accept_listed_comments() {
+        let (result, output) = run("1\n3\n", |reader, writer| {
+            let comment = Comment { id: 3, author: "ana".to_owned(), body: "looks good".to_owned(), created_at: Timestamp(0), edited_at: None };
+            choose_comment_prompt(reader, writer, &[comment])
+        });
+
+        assert_eq!(result.unwrap(), Some(3));
+        assert!(output.contains("3 - ana: looks good"));
+        assert_eq!(output.matches("Invalid comment").count(), 1);
+
+        assert_eq!(run("", |reader, writer| choose_comment_prompt(reader, writer, &[])).0.unwrap(), None);
+    }
+
+    #[test]
+    fn edit_comment_prompt_should_keep_empty_input() {
+        assert_eq!(run("\n", |reader, writer| edit_comment_prompt(reader, writer, "old")).0.unwrap(), None);
+        assert_eq!(run("new\n", |reader, writer| edit_comment_prompt(reader, writer, "old")).0.unwrap(), Some("new".to_owned()));
+    }
+
+    #[test]
+    fn choose_checklist_item_prompt_should_return_position() {
+        let (result, output) = run("0\n3\n2\n", |reader, writer| {
+            let checklist = [ChecklistItem { text: "write".to_owned(), done: true }, ChecklistItem { text: "review".to_owned(), done: false }];
+            choose_checklist_item_prompt(reader, writer, &checklist)
+        });
+
+        assert_eq!(result.unwrap(), Some(1));
+        assert!(output.contains("1 - [x] write"));
+        assert!(output.contains("2 - [ ] review"));
+        assert_eq!(output.matches("Invalid position").count(), 2);
+
+        assert_eq!(run("", |reader, writer| choose_checklist_item_prompt(reader, writer, &[])).0.unwrap(), None);
+    }
+
+    #[test]
+    fn move_checklist_item_prompt_should_return_none_for_empty_input() {
+        assert_eq!(run("\n", |reader, writer| move_checklist_item_prompt(reader, writer, 3)).0.unwrap(), None);
+        assert_eq!(run("3\n", |reader, writer| move_checklist_item_prompt(reader, writer, 3)).0.unwrap(), Some(2));
+    }
+
+    #[test]
+    fn resolve_with_open_items_prompt_should_default_to_no() {
+        let (result, output) = run("\n", |reader, writer| resolve_with_open_items_prompt(reader, writer, 2));
+
+        assert!(!result.unwrap());
+        assert!(output.contains("2 open checklist item(s)"));
+        assert!(run("y\n", |reader, writer| resolve_with_open_items_prompt(reader, writer, 1)).0.unwrap());
+    }
+
+    #[test]
+    fn link_story_prompt_should_build_link_in_chosen_direction() {
+        let (result, output) = run("2\n9\n5\n", |reader, writer| link_story_prompt(reader, writer, 8, &[(5, "login".to_owned())]));
+
+        assert_eq!(result.unwrap(), Some(StoryLink { from: 5, to: 8, link_type: LinkType::Blocks }));
+        assert!(output.contains("5 - login"));
+        assert_eq!(output.matches("Invalid story").count(), 1);
+
+        let (result, _) = run("3\n5\n", |reader, writer| link_story_prompt(reader, writer, 8, &[(5, "login".to_owned())]));
+        assert_eq!(result.unwrap(), Some(StoryLink { from: 8, to: 5, link_type: LinkType::RelatesTo }));
+    }
+
+    #[test]
+    fn link_story_prompt_should_return_none_for_empty_input() {
+        assert_eq!(run("\n", |reader, writer| link_story_prompt(reader, writer, 8, &[(5, "login".to_owned())])).0.unwrap(), None);
+        assert_eq!(run("", |reader, writer| link_story_prompt(reader, writer, 8, &[])).0.unwrap(), None);
+    }
+
+    #[test]
+    fn unlink_story_prompt_should_describe_links_from_the_story() {
+        let (result, output) = run("2\n", |reader, writer| {
+            let links = [StoryLink { from: 8, to: 9, link_type: LinkType::Blocks }, StoryLink { from: 5, to: 8, link_type: LinkType::Blocks }];
+            unlink_story_prompt(reader, writer, 8, &links)
+        });
+
+        assert_eq!(result.unwrap(), Some(StoryLink { from: 5, to: 8, link_type: LinkType::Blocks }));
+        assert!(output.contains("1 - blocks story 9"));
+        assert!(output.contains("2 - blocked by story 5"));
+    }
+
+    #[test]
+    fn close_with_open_blockers_prompt_should_list_blockers() {
+        let (result, output) = run("yes\n", |reader, writer| close_with_open_blockers_prompt(reader, writer, &[5, 7]));
+
+        assert!(result.unwrap());
+        assert!(output.contains("blocked by open stories 5, 7"));
+    }
+
+    #[test]
+    fn create_sprint_prompt_should_ask_again_for_invalid_dates() {
+        let (result, output) = run("sprint 1\nship it\n2024-6\n2024-06-03\n2024-06-02\n2024-06-14\n", create_sprint_prompt);
+
+        assert_eq!(result.unwrap(), Sprint::new("sprint 1".to_owned(), "ship it".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap()));
+        assert!(output.contains("invalid date \"2024-6\""));
+        assert!(output.contains("The sprint cannot end before it starts (2024-06-03)."));
+    }
+
+    #[test]
+    fn assign_sprint_prompt_should_accept_listed_ids_and_backlog() {
+        let (result, output) = run("9\n4\n", |reader, writer| assign_sprint_prompt(reader, writer, &[(4, "sprint 1".to_owned())]));
+
+        assert_eq!(result.unwrap(), Some(Some(4)));
+        assert!(output.contains("4 - sprint 1"));
+        assert_eq!(output.matches("Invalid sprint").count(), 1);
+
+        assert_eq!(run("-\n", |reader, writer| assign_sprint_prompt(reader, writer, &[])).0.unwrap(), Some(None));
+        assert_eq!(run("\n", |reader, writer| assign_sprint_prompt(reader, writer, &[])).0.unwrap(), None);
+    }
+
+    #[test]
+    fn close_sprint_prompt_should_only_confirm_without_unfinished_stories() {
+        let (result, output) = run("y\n", |reader, writer| close_sprint_prompt(reader, writer, 0, &[(4, "sprint 2".to_owned())]));
+
+        assert_eq!(result.unwrap(), Some(None));
+        assert!(!output.contains("4 - sprint 2"));
+        assert_eq!(run("\n", |reader, writer| close_sprint_prompt(reader, writer, 0, &[])).0.unwrap(), None);
+
+        let (result, output) = run("4\n", |reader, writer| close_sprint_prompt(reader, writer, 2, &[(4, "sprint 2".to_owned())]));
+
+        assert_eq!(result.unwrap(), Some(Some(4)));
+        assert!(output.contains("2 unfinished stories will leave this sprint."));
+    }
+}
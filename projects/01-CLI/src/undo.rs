@@ -0,0 +1,588 @@
+use anyhow::{anyhow, Result};
+
+use crate::db::JiraDatabase;
+use crate::models::{ChecklistItem, Comment, CommentTarget, DetailsUpdate, Epic, Sprint, SprintState, Status, Story, StoryLink, StoryPlanning};
+
+/// A mutation of the database. Applying one returns the operation that reverts it,
+/// which is what makes undo and redo symmetric.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    CreateEpic { epic: Epic },
+    RestoreEpic { epic_id: u32, epic: Epic, stories: Vec<(u32, Story)>, links: Vec<StoryLink> },
+    DeleteEpic { epic_id: u32 },
+    CreateStory { epic_id: u32, story: Story },
+    RestoreStory { epic_id: u32, story_id: u32, story: Story, position: usize, links: Vec<StoryLink> },
+    DeleteStory { epic_id: u32, story_id: u32 },
+    MoveStory { story_id: u32, from_epic: u32, to_epic: u32 },
+    // check_workflow is only set for changes made by the user, reverting them is always allowed
+    UpdateEpicStatus { epic_id: u32, status: Status, check_workflow: bool },
+    UpdateStoryStatus { story_id: u32, status: Status, check_workflow: bool },
+    // status is only used when derivation is turned off, to put back the status set by hand
+    SetDerivedStatus { epic_id: u32, derive_status: bool, status: Option<Status> },
+    UpdateEpic { epic_id: u32, update: DetailsUpdate },
+    UpdateStory { story_id: u32, update: DetailsUpdate },
+    UpdateStoryPlanning { story_id: u32, planning: StoryPlanning },
+    AddComment { target: CommentTarget, author: String, body: String },
+    RestoreComment { target: CommentTarget, comment: Comment, position: usize },
+    UpdateComment { target: CommentTarget, comment_id: u32, body: String },
+    DeleteComment { target: CommentTarget, comment_id: u32 },
+    // checklist items are addressed by their zero based position
+    AddChecklistItem { story_id: u32, text: String },
+    RestoreChecklistItem { story_id: u32, item: ChecklistItem, position: usize },
+    ToggleChecklistItem { story_id: u32, position: usize },
+    MoveChecklistItem { story_id: u32, from: usize, to: usize },
+    DeleteChecklistItem { story_id: u32, position: usize },
+    LinkStories { link: StoryLink },
+    UnlinkStories { link: StoryLink },
+    CreateSprint { sprint: Sprint },
+    RestoreSprint { sprint_id: u32, sprint: Sprint },
+    DeleteSprint { sprint_id: u32 },
+    StartSprint { sprint_id: u32 },
+    RestoreSprintState { sprint_id: u32, state: SprintState },
+    CloseSprint { sprint_id: u32, next_sprint_id: Option<u32> },
+    // the stories that moved out of the sprint when it was closed
+    ReopenSprint { sprint_id: u32, story_ids: Vec<u32>, next_sprint_id: Option<u32> },
+    AssignStoryToSprint { story_id: u32, sprint_id: Option<u32>, check_state: bool },
+}
+
+impl Operation {
+    /// Applies the operation to `db` and returns the operation that reverts it.
+    pub fn apply(self, db: &JiraDatabase) -> Result<Operation> {
+        match self {
+            Operation::CreateEpic { epic } => Ok(Operation::DeleteEpic { epic_id: db.create_epic(epic)? }),
+            Operation::RestoreEpic { epic_id, epic, stories, links } => {
+                db.restore_epic(epic_id, epic, stories, links)?;
+                Ok(Operation::DeleteEpic { epic_id })
+            }
+            Operation::DeleteEpic { epic_id } => {
+                let mut db_state = db.read_db()?;
+                let epic = db_state.epics.remove(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?;
+                let stories = epic.stories.iter().filter_map(|id| db_state.stories.remove(id).map(|story| (*id, story))).collect();
+                let links = db_state.links.into_iter().filter(|link| epic.stories.iter().any(|id| link.involves(*id))).collect();
+
+                db.delete_epic(epic_id)?;
+                Ok(Operation::RestoreEpic { epic_id, epic, stories, links })
+            }
+            Operation::CreateStory { epic_id, story } => Ok(Operation::DeleteStory { epic_id, story_id: db.create_story(story, epic_id)? }),
+            Operation::RestoreStory { epic_id, story_id, story, position, links } => {
+                db.restore_story(epic_id, story_id, story, position, links)?;
+                Ok(Operation::DeleteStory { epic_id, story_id })
+            }
+            Operation::DeleteStory { epic_id, story_id } => {
+                let mut db_state = db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?;
+                let position = epic.stories.iter().position(|&id| id == story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?;
+                let story = db_state.stories.remove(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?;
+                let links = db_state.links.into_iter().filter(|link| link.involves(story_id)).collect();
+
+                db.delete_story(epic_id, story_id)?;
+                Ok(Operation::RestoreStory { epic_id, story_id, story, position, links })
+            }
+            Operation::MoveStory { story_id, from_epic, to_epic } => {
+                db.move_story(story_id, from_epic, to_epic)?;
+                Ok(Operation::MoveStory { story_id, from_epic: to_epic, to_epic: from_epic })
+            }
+            Operation::UpdateEpicStatus { epic_id, status, check_workflow } => {
+                let db_state = db.read_db()?;
+                let old_status = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?.status.clone();
+
+                if check_workflow {
+                    db.update_epic_status(epic_id, status)?;
+                } else {
+                    db.restore_epic_status(epic_id, status)?;
+                }
+
+                Ok(Operation::UpdateEpicStatus { epic_id, status: old_status, check_workflow: false })
+            }
+            Operation::UpdateStoryStatus { story_id, status, check_workflow } => {
+                let db_state = db.read_db()?;
+                let old_status = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?.status.clone();
+
+                if check_workflow {
+                    db.update_story_status(story_id, status)?;
+                } else {
+                    db.restore_story_status(story_id, status)?;
+                }
+
+                Ok(Operation::UpdateStoryStatus { story_id, status: old_status, check_workflow: false })
+            }
+            Operation::SetDerivedStatus { epic_id, derive_status, status } => {
+                let db_state = db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?;
+                let inverse = Operation::SetDerivedStatus { epic_id, derive_status: epic.derive_status, status: Some(epic.status.clone()) };
+
+                db.set_derived_status(epic_id, derive_status, status)?;
+                Ok(inverse)
+            }
+            Operation::UpdateEpic { epic_id, update } => {
+                let db_state = db.read_db()?;
+                let epic = db_state.epics.get(&epic_id).ok_or_else(|| anyhow!("Epic {} not found in database", epic_id))?;
+                let old_details = DetailsUpdate { name: Some(epic.name.clone()), description: Some(epic.description.clone()) };
+
+                db.update_epic(epic_id, update)?;
+                Ok(Operation::UpdateEpic { epic_id, update: old_details })
+            }
+            Operation::UpdateStory { story_id, update } => {
+                let db_state = db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?;
+                let old_details = DetailsUpdate { name: Some(story.name.clone()), description: Some(story.description.clone()) };
+
+                db.update_story(story_id, update)?;
+                Ok(Operation::UpdateStory { story_id, update: old_details })
+            }
+            Operation::UpdateStoryPlanning { story_id, planning } => {
+                let db_state = db.read_db()?;
+                let old_planning = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?.planning();
+
+                db.update_story_planning(story_id, planning)?;
+                Ok(Operation::UpdateStoryPlanning { story_id, planning: old_planning })
+            }
+            Operation::AddComment { target, author, body } => Ok(Operation::DeleteComment { target, comment_id: db.add_comment(target, author, body)? }),
+            Operation::RestoreComment { target, comment, position } => {
+                let comment_id = comment.id;
+
+                db.restore_comment(target, comment, position)?;
+                Ok(Operation::DeleteComment { target, comment_id })
+            }
+            Operation::UpdateComment { target, comment_id, body } => {
+                let db_state = db.read_db()?;
+                let comments = db_state.comments(target).ok_or_else(|| anyhow!("{} not found in database", target))?;
+                let old_body = comments.iter().find(|comment| comment.id == comment_id)
+                    .ok_or_else(|| anyhow!("Comment {} not found on {}", comment_id, target))?.body.clone();
+
+                db.update_comment(target, comment_id, body)?;
+                Ok(Operation::UpdateComment { target, comment_id, body: old_body })
+            }
+            Operation::DeleteComment { target, comment_id } => {
+                let db_state = db.read_db()?;
+                let comments = db_state.comments(target).ok_or_else(|| anyhow!("{} not found in database", target))?;
+                let position = comments.iter().position(|comment| comment.id == comment_id)
+                    .ok_or_else(|| anyhow!("Comment {} not found on {}", comment_id, target))?;
+                let comment = comments[position].clone();
+
+                db.delete_comment(target, comment_id)?;
+                Ok(Operation::RestoreComment { target, comment, position })
+            }
+            Operation::AddChecklistItem { story_id, text } => Ok(Operation::DeleteChecklistItem { story_id, position: db.add_checklist_item(story_id, text)? }),
+            Operation::RestoreChecklistItem { story_id, item, position } => {
+                let position = db.restore_checklist_item(story_id, item, position)?;
+                Ok(Operation::DeleteChecklistItem { story_id, position })
+            }
+            Operation::ToggleChecklistItem { story_id, position } => {
+                db.toggle_checklist_item(story_id, position)?;
+                Ok(Operation::ToggleChecklistItem { story_id, position })
+            }
+            Operation::MoveChecklistItem { story_id, from, to } => {
+                db.move_checklist_item(story_id, from, to)?;
+                Ok(Operation::MoveChecklistItem { story_id, from: to, to: from })
+            }
+            Operation::DeleteChecklistItem { story_id, position } => {
+                let db_state = db.read_db()?;
+                let story = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?;
+                let item = story.checklist.get(position).cloned().ok_or_else(|| anyhow!("Checklist item {} not found on story {}", position + 1, story_id))?;
+
+                db.delete_checklist_item(story_id, position)?;
+                Ok(Operation::RestoreChecklistItem { story_id, item, position })
+            }
+            Operation::LinkStories { link } => {
+                db.link_stories(link.clone())?;
+                Ok(Operation::UnlinkStories { link })
+            }
+            Operation::UnlinkStories { link } => {
+                db.unlink_stories(&link)?;
+                Ok(Operation::LinkStories { link })
+            }
+            Operation::CreateSprint { sprint } => Ok(Operation::DeleteSprint { sprint_id: db.create_sprint(sprint)? }),
+            Operation::RestoreSprint { sprint_id, sprint } => {
+                db.restore_sprint(sprint_id, sprint)?;
+                Ok(Operation::DeleteSprint { sprint_id })
+            }
+            Operation::DeleteSprint { sprint_id } => {
+                let db_state = db.read_db()?;
+                let sprint = db_state.sprints.get(&sprint_id).cloned().ok_or_else(|| anyhow!("Sprint {} not found in database", sprint_id))?;
+
+                db.delete_sprint(sprint_id)?;
+                Ok(Operation::RestoreSprint { sprint_id, sprint })
+            }
+            Operation::StartSprint { sprint_id } => {
+                db.start_sprint(sprint_id)?;
+                Ok(Operation::RestoreSprintState { sprint_id, state: SprintState::Planned })
+            }
+            Operation::RestoreSprintState { sprint_id, state } => {
+                let db_state = db.read_db()?;
+                let old_state = db_state.sprints.get(&sprint_id).ok_or_else(|| anyhow!("Sprint {} not found in database", sprint_id))?.state.clone();
+
+                db.restore_sprint_state(sprint_id, state)?;
+                Ok(Operation::RestoreSprintState { sprint_id, state: old_state })
+            }
+            Operation::CloseSprint { sprint_id, next_sprint_id } => {
+                let story_ids = db.close_sprint(sprint_id, next_sprint_id)?;
+                Ok(Operation::ReopenSprint { sprint_id, story_ids, next_sprint_id })
+            }
+            Operation::ReopenSprint { sprint_id, story_ids, next_sprint_id } => {
+                db.reopen_sprint(sprint_id, &story_ids)?;
+                Ok(Operation::CloseSprint { sprint_id, next_sprint_id })
+            }
+            Operation::AssignStoryToSprint { story_id, sprint_id, check_state } => {
+                let db_state = db.read_db()?;
+                let old_sprint_id = db_state.stories.get(&story_id).ok_or_else(|| anyhow!("Story {} not found in database", story_id))?.sprint_id;
+
+                if check_state {
+                    db.assign_story_to_sprint(story_id, sprint_id)?;
+                } else {
+                    db.restore_story_sprint(story_id, sprint_id)?;
+                }
+
+                Ok(Operation::AssignStoryToSprint { story_id, sprint_id: old_sprint_id, check_state: false })
+            }
+        }
+    }
+}
+
+/// The undo and redo stacks of one interactive session.
+#[derive(Debug, Default)]
+pub struct UndoLog {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>
+}
+
+impl UndoLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a new operation. Redo is no longer possible after that.
+    pub fn apply(&mut self, db: &JiraDatabase, operation: Operation) -> Result<()> {
+        let inverse = operation.apply(db)?;
+
+        self.undo.push(inverse);
+        self.redo.clear();
+
+        Ok(())
+    }
+
+    pub fn undo(&mut self, db: &JiraDatabase) -> Result<()> {
+        let operation = self.undo.pop().ok_or_else(|| anyhow!("nothing to undo"))?;
+
+        match operation.clone().apply(db) {
+            Ok(inverse) => self.redo.push(inverse),
+            Err(error) => {
+                // keep the entry so a failed undo (e.g. a write conflict) can be retried
+                self.undo.push(operation);
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn redo(&mut self, db: &JiraDatabase) -> Result<()> {
+        let operation = self.redo.pop().ok_or_else(|| anyhow!("nothing to redo"))?;
+
+        match operation.clone().apply(db) {
+            Ok(inverse) => self.undo.push(inverse),
+            Err(error) => {
+                self.redo.push(operation);
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_utils::MockDB;
+    use crate::models::{Date, LinkType};
+
+    use super::*;
+
+    fn new_db() -> JiraDatabase {
+        JiraDatabase::with_database(Box::new(MockDB::new()))
+    }
+
+    #[test]
+    fn undo_should_restore_deleted_epic_with_original_ids() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let epic_id = db.create_epic(Epic::new("epic".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id).unwrap();
+        let before = db.read_db().unwrap();
+
+        log.apply(&db, Operation::DeleteEpic { epic_id }).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+
+        log.undo(&db).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics, before.epics);
+        assert_eq!(db_state.stories.get(&story_id), before.stories.get(&story_id));
+
+        log.redo(&db).unwrap();
+        assert!(db.read_db().unwrap().stories.is_empty());
+    }
+
+    #[test]
+    fn undo_should_restore_deleted_story_at_its_position() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let first_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let second_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+
+        log.apply(&db, Operation::DeleteStory { epic_id, story_id: first_story_id }).unwrap();
+        log.undo(&db).unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics.get(&epic_id).unwrap().stories, vec![first_story_id, second_story_id]);
+    }
+
+    #[test]
+    fn undo_and_redo_should_revert_created_items() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        log.apply(&db, Operation::CreateEpic { epic: Epic::new("epic".to_owned(), "".to_owned()) }).unwrap();
+        log.apply(&db, Operation::CreateStory { epic_id: 1, story: Story::new("story".to_owned(), "".to_owned()) }).unwrap();
+
+        log.undo(&db).unwrap();
+        log.undo(&db).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+
+        log.redo(&db).unwrap();
+        log.redo(&db).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.get(&1).unwrap().name, "epic");
+        assert_eq!(db_state.stories.get(&2).unwrap().name, "story");
+    }
+
+    #[test]
+    fn undo_and_redo_should_revert_comment_changes() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let target = CommentTarget::Story { story_id };
+
+        log.apply(&db, Operation::AddComment { target, author: "ana".to_owned(), body: "first".to_owned() }).unwrap();
+        db.add_comment(target, "bob".to_owned(), "second".to_owned()).unwrap();
+        log.apply(&db, Operation::UpdateComment { target, comment_id: 1, body: "edited".to_owned() }).unwrap();
+        log.apply(&db, Operation::DeleteComment { target, comment_id: 1 }).unwrap();
+
+        log.undo(&db).unwrap();
+        log.undo(&db).unwrap();
+
+        let comments = db.read_db().unwrap().stories[&story_id].comments.clone();
+        assert_eq!(comments.iter().map(|comment| (comment.id, comment.body.as_str())).collect::<Vec<_>>(), vec![(1, "first"), (2, "second")]);
+
+        log.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].comments.len(), 1);
+
+        log.redo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].comments[0], comments[0]);
+    }
+
+    #[test]
+    fn undo_and_redo_should_revert_checklist_changes() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let texts = |db: &JiraDatabase| db.read_db().unwrap().stories[&story_id].checklist.iter()
+            .map(|item| format!("{}{}", item.text, if item.done { "*" } else { "" })).collect::<Vec<_>>();
+
+        log.apply(&db, Operation::AddChecklistItem { story_id, text: "write".to_owned() }).unwrap();
+        log.apply(&db, Operation::AddChecklistItem { story_id, text: "review".to_owned() }).unwrap();
+        log.apply(&db, Operation::ToggleChecklistItem { story_id, position: 0 }).unwrap();
+        log.apply(&db, Operation::MoveChecklistItem { story_id, from: 1, to: 0 }).unwrap();
+        log.apply(&db, Operation::DeleteChecklistItem { story_id, position: 1 }).unwrap();
+        assert_eq!(texts(&db), vec!["review"]);
+
+        log.undo(&db).unwrap();
+        assert_eq!(texts(&db), vec!["review", "write*"]);
+
+        log.undo(&db).unwrap();
+        log.undo(&db).unwrap();
+        assert_eq!(texts(&db), vec!["write", "review"]);
+
+        log.redo(&db).unwrap();
+        log.redo(&db).unwrap();
+        log.redo(&db).unwrap();
+        assert_eq!(texts(&db), vec!["review"]);
+    }
+
+    #[test]
+    fn undo_should_restore_links_of_deleted_stories() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let first_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let second_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let first_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), first_epic_id).unwrap();
+        let second_story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), second_epic_id).unwrap();
+        let link = StoryLink { from: first_story_id, to: second_story_id, link_type: LinkType::Blocks };
+
+        log.apply(&db, Operation::LinkStories { link: link.clone() }).unwrap();
+
+        log.apply(&db, Operation::DeleteStory { epic_id: second_epic_id, story_id: second_story_id }).unwrap();
+        log.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().links, vec![link.clone()]);
+
+        log.apply(&db, Operation::DeleteEpic { epic_id: first_epic_id }).unwrap();
+        assert!(db.read_db().unwrap().links.is_empty());
+        log.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().links, vec![link]);
+
+        log.undo(&db).unwrap();
+        assert!(db.read_db().unwrap().links.is_empty());
+    }
+
+    #[test]
+    fn undo_and_redo_should_revert_sprint_changes() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+        let sprint = |name: &str| Sprint::new(name.to_owned(), "".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap());
+
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        let sprint_id = db.create_sprint(sprint("first")).unwrap();
+
+        log.apply(&db, Operation::CreateSprint { sprint: sprint("second") }).unwrap();
+        log.apply(&db, Operation::AssignStoryToSprint { story_id, sprint_id: Some(sprint_id), check_state: true }).unwrap();
+        log.apply(&db, Operation::StartSprint { sprint_id }).unwrap();
+        log.apply(&db, Operation::CloseSprint { sprint_id, next_sprint_id: Some(4) }).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.sprints[&sprint_id].state, SprintState::Closed);
+        assert_eq!(db_state.stories[&story_id].sprint_id, Some(4));
+
+        log.undo(&db).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.sprints[&sprint_id].state, SprintState::Active);
+        assert_eq!(db_state.stories[&story_id].sprint_id, Some(sprint_id));
+
+        log.redo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].sprint_id, Some(4));
+
+        for _ in 0..4 {
+            log.undo(&db).unwrap();
+        }
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.sprints.len(), 1);
+        assert_eq!(db_state.sprints[&sprint_id].state, SprintState::Planned);
+        assert_eq!(db_state.stories[&story_id].sprint_id, None);
+    }
+
+    #[test]
+    fn undo_should_restore_deleted_sprint() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+        let sprint = Sprint::new("sprint".to_owned(), "goal".to_owned(), Date::new(2024, 6, 3).unwrap(), Date::new(2024, 6, 14).unwrap());
+
+        let sprint_id = db.create_sprint(sprint).unwrap();
+        let created = db.read_db().unwrap().sprints[&sprint_id].clone();
+
+        log.apply(&db, Operation::DeleteSprint { sprint_id }).unwrap();
+        assert!(db.read_db().unwrap().sprints.is_empty());
+
+        log.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().sprints.get(&sprint_id), Some(&created));
+    }
+
+    #[test]
+    fn undo_should_restore_status_set_by_hand() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id).unwrap();
+        db.update_epic_status(epic_id, Status::InProgress).unwrap();
+
+        log.apply(&db, Operation::SetDerivedStatus { epic_id, derive_status: true, status: None }).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].status, Status::Open);
+
+        log.apply(&db, Operation::UpdateStoryStatus { story_id, status: Status::InProgress, check_workflow: true }).unwrap();
+        log.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].status, Status::Open);
+
+        log.undo(&db).unwrap();
+
+        let epic = db.read_db().unwrap().epics[&epic_id].clone();
+        assert!(!epic.derive_status);
+        assert_eq!(epic.status, Status::InProgress);
+
+        log.redo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].status, Status::Open);
+    }
+
+    #[test]
+    fn undo_should_revert_status_even_against_the_workflow() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        db.update_epic_status(epic_id, Status::InProgress).unwrap();
+        db.update_epic_status(epic_id, Status::Resolved).unwrap();
+
+        log.apply(&db, Operation::UpdateEpicStatus { epic_id, status: Status::Closed, check_workflow: true }).unwrap();
+        log.undo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().epics.get(&epic_id).unwrap().status, Status::Resolved);
+
+        log.redo(&db).unwrap();
+        assert_eq!(db.read_db().unwrap().epics.get(&epic_id).unwrap().status, Status::Closed);
+    }
+
+    #[test]
+    fn undo_should_revert_details_planning_and_moves() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        let first_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let second_epic_id = db.create_epic(Epic::new("".to_owned(), "".to_owned())).unwrap();
+        let story_id = db.create_story(Story::new("name".to_owned(), "description".to_owned()), first_epic_id).unwrap();
+        let before = db.read_db().unwrap().stories[&story_id].clone();
+
+        log.apply(&db, Operation::UpdateStory { story_id, update: DetailsUpdate { name: Some("new name".to_owned()), description: None } }).unwrap();
+        log.apply(&db, Operation::UpdateStoryPlanning { story_id, planning: StoryPlanning { story_points: Some(5), ..StoryPlanning::default() } }).unwrap();
+        log.apply(&db, Operation::MoveStory { story_id, from_epic: first_epic_id, to_epic: second_epic_id }).unwrap();
+
+        log.undo(&db).unwrap();
+        log.undo(&db).unwrap();
+        log.undo(&db).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+
+        assert_eq!(story.name, before.name);
+        assert_eq!(story.description, before.description);
+        assert_eq!(story.planning(), before.planning());
+        assert_eq!(db_state.epics.get(&first_epic_id).unwrap().stories, vec![story_id]);
+    }
+
+    #[test]
+    fn apply_should_clear_redo() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        log.apply(&db, Operation::CreateEpic { epic: Epic::new("".to_owned(), "".to_owned()) }).unwrap();
+        log.undo(&db).unwrap();
+        log.apply(&db, Operation::CreateEpic { epic: Epic::new("".to_owned(), "".to_owned()) }).unwrap();
+
+        assert!(log.redo(&db).is_err());
+    }
+
+    #[test]
+    fn undo_should_fail_when_empty() {
+        let db = new_db();
+        let mut log = UndoLog::new();
+
+        assert!(log.undo(&db).is_err());
+        assert!(log.redo(&db).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::models::Status;
+
+/// The transitions allowed between statuses. Moving an item to the status it
+/// already has is always allowed, since nothing changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Workflow {
+    transitions: HashMap<Status, HashSet<Status>>
+}
+
+impl Workflow {
+    /// A workflow without any transitions. Use `allow` to add them.
+    pub fn new() -> Self {
+        Self { transitions: HashMap::new() }
+    }
+
+    pub fn allow(mut self, from: Status, to: Status) -> Self {
+        self.transitions.entry(from).or_default().insert(to);
+        self
+    }
+
+    pub fn is_allowed(&self, from: &Status, to: &Status) -> bool {
+        from == to || self.transitions.get(from).is_some_and(|targets| targets.contains(to))
+    }
+
+    pub fn check(&self, from: &Status, to: &Status) -> Result<(), IllegalTransitionError> {
+        if !self.is_allowed(from, to) {
+            return Err(IllegalTransitionError { from: from.clone(), to: to.clone() });
+        }
+
+        Ok(())
+    }
+
+    /// Reads the transitions from a JSON object that maps each status to the
+    /// statuses it can move to, e.g. `{"open": ["in-progress"], "in-progress": ["open", "resolved"]}`.
+    /// Statuses are spelled like on the command line.
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let transitions: BTreeMap<String, Vec<String>> = serde_json::from_str(contents)?;
+
+        transitions.iter().try_fold(Self::new(), |workflow, (from, targets)| {
+            let from: Status = from.parse()?;
+
+            targets.iter().try_fold(workflow, |workflow, to| Ok(workflow.allow(from.clone(), to.parse()?)))
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        fs::read_to_string(path).map_err(anyhow::Error::from).and_then(|contents| Self::from_json(&contents))
+            .with_context(|| format!("failed to load workflow from {}", path))
+    }
+
+    /// The statuses an item in `from` can move to, in `Status::ALL` order.
+    pub fn next_statuses(&self, from: &Status) -> Vec<Status> {
+        Status::ALL.into_iter().filter(|to| to != from && self.is_allowed(from, to)).collect()
+    }
+}
+
+impl Default for Workflow {
+    // Open -> In Progress -> Resolved -> Closed, with explicit ways back to Open
+    fn default() -> Self {
+        Self::new()
+            .allow(Status::Open, Status::InProgress)
+            .allow(Status::InProgress, Status::Open)
+            .allow(Status::InProgress, Status::Resolved)
+            .allow(Status::Resolved, Status::Closed)
+            .allow(Status::Resolved, Status::Open)
+            .allow(Status::Closed, Status::Open)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct IllegalTransitionError {
+    pub from: Status,
+    pub to: Status
+}
+
+impl Display for IllegalTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot move from {} to {}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for IllegalTransitionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_workflow_should_follow_happy_path() {
+        let workflow = Workflow::default();
+
+        assert!(workflow.check(&Status::Open, &Status::InProgress).is_ok());
+        assert!(workflow.check(&Status::InProgress, &Status::Resolved).is_ok());
+        assert!(workflow.check(&Status::Resolved, &Status::Closed).is_ok());
+    }
+
+    #[test]
+    fn default_workflow_should_allow_reopen() {
+        let workflow = Workflow::default();
+
+        assert!(workflow.check(&Status::Resolved, &Status::Open).is_ok());
+        assert!(workflow.check(&Status::Closed, &Status::Open).is_ok());
+    }
+
+    #[test]
+    fn default_workflow_should_reject_skipping_states() {
+        let workflow = Workflow::default();
+
+        assert_eq!(workflow.check(&Status::Open, &Status::Closed), Err(IllegalTransitionError { from: Status::Open, to: Status::Closed }));
+        assert!(workflow.check(&Status::Closed, &Status::InProgress).is_err());
+        assert!(workflow.check(&Status::Open, &Status::Resolved).is_err());
+    }
+
+    #[test]
+    fn check_should_allow_keeping_the_same_status() {
+        let workflow = Workflow::new();
+
+        assert!(workflow.check(&Status::Closed, &Status::Closed).is_ok());
+    }
+
+    #[test]
+    fn next_statuses_should_list_legal_targets_in_order() {
+        let workflow = Workflow::default();
+
+        assert_eq!(workflow.next_statuses(&Status::Open), vec![Status::InProgress]);
+        assert_eq!(workflow.next_statuses(&Status::Resolved), vec![Status::Open, Status::Closed]);
+        assert_eq!(Workflow::new().allow(Status::Open, Status::Closed).allow(Status::Open, Status::InProgress).next_statuses(&Status::Open),
+            vec![Status::InProgress, Status::Closed]);
+        assert!(Workflow::new().next_statuses(&Status::Open).is_empty());
+    }
+
+    #[test]
+    fn from_json_should_read_transitions() {
+        let workflow = Workflow::from_json(r#"{ "open": ["in-progress", "Closed"], "CLOSED": ["open"] }"#).unwrap();
+
+        assert_eq!(workflow, Workflow::new().allow(Status::Open, Status::InProgress).allow(Status::Open, Status::Closed).allow(Status::Closed, Status::Open));
+    }
+
+    #[test]
+    fn from_json_should_reject_unknown_statuses() {
+        assert!(Workflow::from_json(r#"{ "open": ["done"] }"#).is_err());
+        assert!(Workflow::from_json(r#"{ "todo": [] }"#).is_err());
+        assert!(Workflow::from_json("[]").is_err());
+    }
+
+    #[test]
+    fn load_should_name_the_file_in_errors() {
+        let error = Workflow::load("INVALID_PATH").unwrap_err();
+
+        assert!(format!("{:#}", error).starts_with("failed to load workflow from INVALID_PATH"));
+    }
+}